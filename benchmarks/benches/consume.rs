@@ -0,0 +1,154 @@
+use benchmarks::*;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rust_fsm::{AtomicStateMachine, SharedStateMachine, StateMachine, StateMachineImpl};
+use std::{hint::black_box, sync::Mutex};
+
+/// The number of inputs consumed by the large machines in one iteration.
+const LARGE_SEQUENCE: usize = 64;
+
+/// Consume the inputs in a loop. The machine is expected to accept all of them
+/// in any state it reaches.
+fn consume_all<T: StateMachineImpl>(machine: &mut StateMachine<T>, inputs: &[T::Input]) {
+    for input in inputs {
+        black_box(machine.consume(black_box(input)).unwrap());
+    }
+}
+
+/// The inputs bring the circuit breaker back into the `Closed` state.
+fn small(c: &mut Criterion) {
+    let mut group = c.benchmark_group("small");
+    group.throughput(Throughput::Elements(3));
+
+    let inputs = [
+        circuit_breaker::Input::Unsuccessful,
+        circuit_breaker::Input::TimerTriggered,
+        circuit_breaker::Input::Successful,
+    ];
+    let mut machine = circuit_breaker::StateMachine::new();
+    group.bench_function("match", |b| b.iter(|| consume_all(&mut machine, &inputs)));
+
+    let inputs = [
+        circuit_breaker_table::Input::Unsuccessful,
+        circuit_breaker_table::Input::TimerTriggered,
+        circuit_breaker_table::Input::Successful,
+    ];
+    let mut machine = circuit_breaker_table::StateMachine::new();
+    group.bench_function("table", |b| b.iter(|| consume_all(&mut machine, &inputs)));
+
+    let inputs = [
+        Input::Unsuccessful,
+        Input::TimerTriggered,
+        Input::Successful,
+    ];
+    let mut machine = circuit_breaker_custom::StateMachine::new();
+    group.bench_function("custom_types", |b| {
+        b.iter(|| consume_all(&mut machine, &inputs))
+    });
+
+    group.finish();
+}
+
+/// A fixed pseudo-random sequence of inputs.
+fn large_inputs<I>(next: I, back: I, skip: I, reset: I) -> Vec<I>
+where
+    I: Clone,
+{
+    let choices = [next, back, skip, reset];
+    let mut seed = 0x2545_f491_u32;
+    (0..LARGE_SEQUENCE)
+        .map(|_| {
+            seed ^= seed << 13;
+            seed ^= seed >> 17;
+            seed ^= seed << 5;
+            // Reset rarely so that the machine moves around the ring.
+            let index = (seed % 16).min(3) as usize;
+            choices[index].clone()
+        })
+        .collect()
+}
+
+fn large(c: &mut Criterion) {
+    let mut group = c.benchmark_group("large");
+    group.throughput(Throughput::Elements(LARGE_SEQUENCE as u64));
+
+    let inputs = large_inputs(
+        large_match::Input::Next,
+        large_match::Input::Back,
+        large_match::Input::Skip,
+        large_match::Input::Reset,
+    );
+    let mut machine = large_match::StateMachine::new();
+    group.bench_function("match", |b| b.iter(|| consume_all(&mut machine, &inputs)));
+
+    let inputs = large_inputs(
+        large_table::Input::Next,
+        large_table::Input::Back,
+        large_table::Input::Skip,
+        large_table::Input::Reset,
+    );
+    let mut machine = large_table::StateMachine::new();
+    group.bench_function("table", |b| b.iter(|| consume_all(&mut machine, &inputs)));
+
+    group.finish();
+}
+
+/// The wrappers sharing a machine between threads, used from a single thread to
+/// measure their overhead.
+fn wrappers(c: &mut Criterion) {
+    let mut group = c.benchmark_group("wrappers");
+    group.throughput(Throughput::Elements(3));
+    let inputs = [
+        circuit_breaker::Input::Unsuccessful,
+        circuit_breaker::Input::TimerTriggered,
+        circuit_breaker::Input::Successful,
+    ];
+
+    let mut machine = circuit_breaker::StateMachine::new();
+    group.bench_function("state_machine", |b| {
+        b.iter(|| consume_all(&mut machine, &inputs))
+    });
+
+    let machine = Mutex::new(circuit_breaker::StateMachine::new());
+    group.bench_function("mutex", |b| {
+        b.iter(|| consume_all(&mut machine.lock().unwrap(), &inputs))
+    });
+
+    let machine = SharedStateMachine::<circuit_breaker::Impl>::new();
+    group.bench_function("shared", |b| {
+        b.iter(|| {
+            for input in &inputs {
+                black_box(machine.consume(black_box(input)).unwrap());
+            }
+        })
+    });
+
+    let machine = AtomicStateMachine::<circuit_breaker::Impl>::new();
+    group.bench_function("atomic", |b| {
+        b.iter(|| {
+            for input in &inputs {
+                black_box(machine.consume(black_box(input)).unwrap());
+            }
+        })
+    });
+
+    // The hot path of a closed circuit breaker: the requests succeed and the
+    // input is rejected without a transition.
+    let successful = circuit_breaker::Input::Successful;
+    let machine = Mutex::new(circuit_breaker::StateMachine::new());
+    group.bench_function(BenchmarkId::new("rejected", "mutex"), |b| {
+        b.iter(|| black_box(machine.lock().unwrap().consume(black_box(&successful))))
+    });
+    let machine = SharedStateMachine::<circuit_breaker::Impl>::new();
+    group.bench_function(BenchmarkId::new("rejected", "shared"), |b| {
+        b.iter(|| black_box(machine.consume(black_box(&successful))))
+    });
+    let machine = AtomicStateMachine::<circuit_breaker::Impl>::new();
+    group.bench_function(BenchmarkId::new("rejected", "atomic"), |b| {
+        b.iter(|| black_box(machine.consume(black_box(&successful))))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, small, large, wrappers);
+criterion_main!(benches);
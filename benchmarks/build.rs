@@ -0,0 +1,34 @@
+use rust_fsm_build::{Backend, Machine, Transition};
+
+/// The number of states of the large machines.
+const NUM_STATES: usize = 500;
+
+/// A ring of states where every state accepts every input, so that any
+/// sequence of inputs can be consumed.
+fn large(name: &str, backend: Backend) -> Machine {
+    let state = |index: usize| format!("S{}", index % NUM_STATES);
+    let transitions = (0..NUM_STATES)
+        .flat_map(|i| {
+            [
+                ("Next", state(i + 1), None),
+                ("Back", state(i + NUM_STATES - 1), None),
+                ("Skip", state(i * 7 + 3), Some("Skipped")),
+                ("Reset", state(0), Some("Restarted")),
+            ]
+            .into_iter()
+            .map(move |(input, to, output)| {
+                Transition::new(state(i), input, to).with_outputs(output)
+            })
+        })
+        .collect();
+    let mut machine = Machine::new(name, "S0", transitions);
+    machine.visibility = "pub".to_string();
+    machine.attributes.push("derive(Clone, Copy)".to_string());
+    machine.options.backend = backend;
+    machine
+}
+
+fn main() {
+    rust_fsm_build::compile_machine(&large("large_match", Backend::Match)).unwrap();
+    rust_fsm_build::compile_machine(&large("large_table", Backend::Table)).unwrap();
+}
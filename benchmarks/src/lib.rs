@@ -0,0 +1,57 @@
+//! The machines measured by the benchmarks.
+
+use rust_fsm::state_machine;
+
+state_machine! {
+    pub circuit_breaker(Closed)
+
+    Closed(Unsuccessful) => Open [SetupTimer],
+    Open(TimerTriggered) => HalfOpen,
+    HalfOpen => {
+        Successful => Closed,
+        Unsuccessful => Open [SetupTimer]
+    }
+}
+
+state_machine! {
+    #[state_machine(backend = "table")]
+    pub circuit_breaker_table(Closed)
+
+    Closed(Unsuccessful) => Open [SetupTimer],
+    Open(TimerTriggered) => HalfOpen,
+    HalfOpen => {
+        Successful => Closed,
+        Unsuccessful => Open [SetupTimer]
+    }
+}
+
+pub enum Input {
+    Successful,
+    Unsuccessful,
+    TimerTriggered,
+}
+
+pub enum State {
+    Closed,
+    HalfOpen,
+    Open,
+}
+
+pub enum Output {
+    SetupTimer,
+}
+
+state_machine! {
+    #[state_machine(input(crate::Input), state(crate::State), output(crate::Output))]
+    pub circuit_breaker_custom(Closed)
+
+    Closed(Unsuccessful) => Open [SetupTimer],
+    Open(TimerTriggered) => HalfOpen,
+    HalfOpen => {
+        Successful => Closed,
+        Unsuccessful => Open [SetupTimer]
+    }
+}
+
+include!(concat!(env!("OUT_DIR"), "/large_match.rs"));
+include!(concat!(env!("OUT_DIR"), "/large_table.rs"));
@@ -0,0 +1,25 @@
+use rust_fsm_build::{Machine, Transition};
+
+fn main() {
+    rust_fsm_build::compile("machines/circuit_breaker.fsm").unwrap();
+
+    let transitions = [
+        ("Locked", "Coin", "Unlocked", Some("Unlock")),
+        ("Locked", "Push", "Locked", None),
+        ("Unlocked", "Push", "Locked", Some("Lock")),
+        ("Unlocked", "Coin", "Unlocked", Some("Refund")),
+    ];
+    let mut turnstile = Machine::new(
+        "turnstile",
+        "Locked",
+        transitions
+            .into_iter()
+            .map(|(from, input, to, output)| Transition::new(from, input, to).with_outputs(output))
+            .collect(),
+    );
+    turnstile.visibility = "pub".to_string();
+    turnstile
+        .attributes
+        .push("derive(Debug, PartialEq)".to_string());
+    rust_fsm_build::compile_machine(&turnstile).unwrap();
+}
@@ -0,0 +1,4 @@
+//! State machines generated by a build script with `rust-fsm-build`.
+
+include!(concat!(env!("OUT_DIR"), "/circuit_breaker.rs"));
+include!(concat!(env!("OUT_DIR"), "/turnstile.rs"));
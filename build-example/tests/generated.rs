@@ -0,0 +1,33 @@
+use build_example::{circuit_breaker, turnstile};
+
+#[test]
+fn circuit_breaker() {
+    let mut machine = circuit_breaker::StateMachine::new();
+    let output = machine
+        .consume(&circuit_breaker::Input::Unsuccessful)
+        .unwrap();
+    assert_eq!(output, Some(circuit_breaker::Output::SetupTimer));
+    assert_eq!(machine.state(), &circuit_breaker::State::Open);
+    machine
+        .consume(&circuit_breaker::Input::TimerTriggered)
+        .unwrap();
+    machine
+        .consume(&circuit_breaker::Input::Successful)
+        .unwrap();
+    assert_eq!(machine.state(), &circuit_breaker::State::Closed);
+    assert!(machine
+        .consume(&circuit_breaker::Input::TimerTriggered)
+        .is_err());
+}
+
+#[test]
+fn machine_built_in_code() {
+    let mut machine = turnstile::StateMachine::new();
+    let output = machine.consume(&turnstile::Input::Coin).unwrap();
+    assert_eq!(output, Some(turnstile::Output::Unlock));
+    let output = machine.consume(&turnstile::Input::Coin).unwrap();
+    assert_eq!(output, Some(turnstile::Output::Refund));
+    let output = machine.consume(&turnstile::Input::Push).unwrap();
+    assert_eq!(output, Some(turnstile::Output::Lock));
+    assert_eq!(machine.state(), &turnstile::State::Locked);
+}
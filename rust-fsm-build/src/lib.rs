@@ -0,0 +1,172 @@
+//! Generation of `rust-fsm` state machines in build scripts.
+//!
+//! This produces the same module as the `state_machine` macro, but as a source
+//! file written into `OUT_DIR`. This is useful when procedural macros are not
+//! desirable, e.g. to keep the incremental builds fast or to review the
+//! generated code. The machine is defined either in a file using the DSL (the
+//! same syntax as the contents of the `state_machine` macro) or with the
+//! [`Machine`] model.
+//!
+//! In `build.rs`:
+//!
+//! ```rust,no_run
+//! rust_fsm_build::compile("machines/circuit_breaker.fsm").unwrap();
+//! ```
+//!
+//! In the crate:
+//!
+//! ```rust,ignore
+//! include!(concat!(env!("OUT_DIR"), "/circuit_breaker.rs"));
+//! ```
+//!
+//! The generated module refers to the `rust_fsm` crate, which must be a
+//! dependency of the crate.
+
+use proc_macro2::Span;
+use rust_fsm_dsl_core::{codegen, parser::StateMachineDef, source};
+use std::{
+    error, fmt, fs, io,
+    path::{Path, PathBuf},
+};
+use syn::LitStr;
+
+pub use rust_fsm_dsl_core::model::{Backend, DefaultPolicy, Machine, Options, Transition};
+
+/// The reasons the code generation may fail.
+#[derive(Debug)]
+pub enum Error {
+    /// Reading the definition or writing the generated code failed.
+    Io(io::Error),
+    /// The definition is invalid.
+    Definition(String),
+    /// The output directory is not set: `OUT_DIR` is only available in build
+    /// scripts, elsewhere it must be set with [`Builder::out_dir`].
+    OutDir,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "{error}"),
+            Error::Definition(error) => write!(f, "invalid state machine definition: {error}"),
+            Error::OutDir => write!(f, "OUT_DIR is not set"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+/// Generates state machines with custom settings.
+#[derive(Debug, Clone, Default)]
+pub struct Builder {
+    out_dir: Option<PathBuf>,
+    doc_diagrams: bool,
+}
+
+impl Builder {
+    /// Create a builder writing into `OUT_DIR` without diagrams in the
+    /// documentation.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Write the generated files into the given directory instead of
+    /// `OUT_DIR`.
+    pub fn out_dir(mut self, out_dir: impl Into<PathBuf>) -> Self {
+        self.out_dir = Some(out_dir.into());
+        self
+    }
+
+    /// Include the diagrams selected in the definition into the documentation.
+    /// This requires the `diagram` feature of `rust-fsm`.
+    pub fn doc_diagrams(mut self, doc_diagrams: bool) -> Self {
+        self.doc_diagrams = doc_diagrams;
+        self
+    }
+
+    /// Generate the machine defined with the DSL in the given file. The output
+    /// is written into `<name of the machine>.rs`. Outputs the path of the
+    /// generated file. The files included by the definition are relative to
+    /// the including file. Cargo is instructed to rerun the build script when
+    /// any of the files changes, including when the definition is invalid.
+    pub fn compile(&self, path: impl AsRef<Path>) -> Result<PathBuf, Error> {
+        let path = path.as_ref();
+        // A missing definition is an I/O error rather than an invalid one.
+        fs::metadata(path)?;
+        let literal = LitStr::new(&path.to_string_lossy(), Span::call_site());
+        // The files read before an error are tracked too, so that fixing any
+        // of them reruns the build script.
+        let mut files = Vec::new();
+        let result = source::load_dsl_tracked_in(Path::new(""), &literal, &mut files);
+        if files.is_empty() {
+            files.push(path.display().to_string());
+        }
+        for file in &files {
+            println!("cargo:rerun-if-changed={file}");
+        }
+        let mut def = result.map_err(|error| Error::Definition(error.to_string()))?;
+        // The files are tracked by Cargo, the generated code cannot refer to
+        // them by relative paths.
+        def.source_files.clear();
+        self.write(&def)
+    }
+
+    /// Generate the given machine. The output is written into `<name of the
+    /// machine>.rs`. Outputs the path of the generated file.
+    pub fn compile_machine(&self, machine: &Machine) -> Result<PathBuf, Error> {
+        let def = StateMachineDef::try_from(machine)
+            .map_err(|error| Error::Definition(error.to_string()))?;
+        self.write(&def)
+    }
+
+    /// Generate the source code of the given machine.
+    pub fn generate(&self, machine: &Machine) -> Result<String, Error> {
+        let def = StateMachineDef::try_from(machine)
+            .map_err(|error| Error::Definition(error.to_string()))?;
+        self.source(&def)
+    }
+
+    fn write(&self, def: &StateMachineDef) -> Result<PathBuf, Error> {
+        let out_dir = match &self.out_dir {
+            Some(out_dir) => out_dir.clone(),
+            None => std::env::var_os("OUT_DIR").ok_or(Error::OutDir)?.into(),
+        };
+        let path = out_dir.join(format!("{}.rs", def.name));
+        fs::write(&path, self.source(def)?)?;
+        Ok(path)
+    }
+
+    fn source(&self, def: &StateMachineDef) -> Result<String, Error> {
+        let tokens = codegen::generate(def, self.doc_diagrams)
+            .map_err(|error| Error::Definition(error.to_string()))?;
+        let file = syn::parse2(tokens).map_err(|error| Error::Definition(error.to_string()))?;
+        Ok(format!(
+            "// This file is generated by rust-fsm-build. Do not edit.\n\n{}",
+            prettyplease::unparse(&file)
+        ))
+    }
+}
+
+/// Generate the machine defined with the DSL in the given file into `OUT_DIR`.
+/// See [`Builder::compile`].
+pub fn compile(path: impl AsRef<Path>) -> Result<PathBuf, Error> {
+    Builder::new().compile(path)
+}
+
+/// Generate the given machine into `OUT_DIR`. See [`Builder::compile_machine`].
+pub fn compile_machine(machine: &Machine) -> Result<PathBuf, Error> {
+    Builder::new().compile_machine(machine)
+}
@@ -0,0 +1,118 @@
+use rust_fsm_build::{Builder, Error, Machine, Transition};
+use std::{fs, path::PathBuf};
+
+fn out_dir(test: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("rust-fsm-build-{test}"));
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn door() -> Machine {
+    Machine::new(
+        "door",
+        "Open",
+        vec![
+            Transition::new("Open", "Close", "Closed"),
+            Transition::new("Closed", "Open", "Open").with_outputs(["Creak"]),
+        ],
+    )
+}
+
+#[test]
+fn compile_file() {
+    let dir = out_dir("compile-file");
+    let path = Builder::new()
+        .out_dir(&dir)
+        .compile("tests/machines/circuit_breaker.fsm")
+        .unwrap();
+    assert_eq!(path, dir.join("circuit_breaker.rs"));
+
+    let source = fs::read_to_string(path).unwrap();
+    assert!(source.starts_with("// This file is generated by rust-fsm-build."));
+    assert!(source.contains("pub mod circuit_breaker {"));
+    assert!(source.contains("impl ::rust_fsm::StateMachineImpl for Impl {"));
+    // The documentation is kept.
+    assert!(source.contains("/// https://martinfowler.com/bliki/CircuitBreaker.html"));
+    // The output is formatted.
+    assert!(source.contains("\n    pub enum State {\n"));
+}
+
+#[test]
+fn compile_included() {
+    let dir = out_dir("compile-included");
+    let path = Builder::new()
+        .out_dir(&dir)
+        .compile("tests/machines/nested/included.fsm")
+        .unwrap();
+    let source = fs::read_to_string(path).unwrap();
+    assert!(source.contains("pub mod circuit_breaker {"));
+    // The files are tracked by Cargo rather than by the generated code.
+    assert!(!source.contains("include_bytes!"));
+}
+
+#[test]
+fn compile_machine() {
+    let dir = out_dir("compile-machine");
+    let path = Builder::new()
+        .out_dir(&dir)
+        .compile_machine(&door())
+        .unwrap();
+    assert_eq!(path, dir.join("door.rs"));
+    assert_eq!(
+        fs::read_to_string(path).unwrap(),
+        Builder::new().generate(&door()).unwrap()
+    );
+}
+
+#[test]
+fn same_as_dsl() {
+    let text = fs::read_to_string("tests/machines/circuit_breaker.fsm").unwrap();
+    let machine = Machine::parse_str(&text).unwrap();
+    let from_machine = Builder::new().generate(&machine).unwrap();
+
+    let dir = out_dir("same-as-dsl");
+    let path = Builder::new()
+        .out_dir(&dir)
+        .compile("tests/machines/circuit_breaker.fsm")
+        .unwrap();
+    assert_eq!(fs::read_to_string(path).unwrap(), from_machine);
+}
+
+#[test]
+fn doc_diagrams() {
+    let without = Builder::new().generate(&door()).unwrap();
+    assert!(!without.contains("mermaid"));
+    let with = Builder::new().doc_diagrams(true).generate(&door()).unwrap();
+    assert!(with.contains("mermaid"));
+}
+
+#[test]
+fn invalid_definition() {
+    let error = Builder::new()
+        .out_dir(out_dir("invalid-definition"))
+        .compile("tests/machines/invalid.fsm")
+        .unwrap_err();
+    assert!(matches!(error, Error::Definition(_)));
+    assert_eq!(
+        error.to_string(),
+        "invalid state machine definition: tests/machines/invalid.fsm:4:1: expected `=>`"
+    );
+
+    let mut machine = door();
+    machine.transitions[0].to = "Not a state".to_string();
+    let error = Builder::new().generate(&machine).unwrap_err();
+    assert!(matches!(error, Error::Definition(_)));
+}
+
+#[test]
+fn missing_file() {
+    let error = rust_fsm_build::compile("tests/machines/missing.fsm").unwrap_err();
+    assert!(matches!(error, Error::Io(_)));
+}
+
+#[test]
+fn missing_out_dir() {
+    // `OUT_DIR` is only set for build scripts.
+    let error = rust_fsm_build::compile_machine(&door()).unwrap_err();
+    assert!(matches!(error, Error::OutDir));
+}
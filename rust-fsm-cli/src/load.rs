@@ -0,0 +1,105 @@
+//! Loading machine definitions from `.fsm` and `.rs` files.
+
+use rust_fsm_dsl_core::{
+    parser::StateMachineDef,
+    source::{self, Include},
+    Machine,
+};
+use std::path::{Path, PathBuf};
+use syn::visit::Visit;
+
+/// A machine definition along with the file it was found in.
+pub struct Loaded {
+    pub path: PathBuf,
+    pub machine: Machine,
+}
+
+/// Load all machines defined in the file. `.fsm` files contain exactly one
+/// definition, other files are parsed as Rust code and every invocation of the
+/// `state_machine` macro is loaded.
+pub fn load(path: &Path) -> Result<Vec<Loaded>, String> {
+    let text =
+        std::fs::read_to_string(path).map_err(|error| format!("{}: {error}", path.display()))?;
+
+    let located = |error: syn::Error| {
+        let start = error.span().start();
+        format!(
+            "{}:{}:{}: {error}",
+            path.display(),
+            start.line,
+            start.column + 1
+        )
+    };
+
+    // The errors in the included files are located by the loader.
+    let load_included = |base_dir: &Path, included: &syn::LitStr| {
+        source::load_dsl_in(base_dir, included).map_err(|error| error.to_string())
+    };
+
+    let defs = if path.extension().is_some_and(|extension| extension == "fsm") {
+        // Included files are relative to the including file.
+        match syn::parse_str::<Include>(&text) {
+            Ok(Include(included)) => {
+                let base_dir = path.parent().unwrap_or(Path::new(""));
+                vec![load_included(base_dir, &included)?]
+            }
+            Err(_) => vec![syn::parse_str::<StateMachineDef>(&text).map_err(located)?],
+        }
+    } else {
+        let file = syn::parse_file(&text).map_err(located)?;
+        let mut visitor = MacroVisitor::default();
+        visitor.visit_file(&file);
+        visitor
+            .macros
+            .iter()
+            .map(|mac| match mac.parse_body::<Include>() {
+                // Included files are resolved the same way as by the macro:
+                // relative to the directory of the crate containing the file.
+                Ok(Include(included)) => {
+                    let manifest_dir = manifest_dir(path).ok_or_else(|| {
+                        format!("{}: cannot find the crate of the file", path.display())
+                    })?;
+                    load_included(&manifest_dir, &included)
+                }
+                Err(_) => mac.parse_body::<StateMachineDef>().map_err(located),
+            })
+            .collect::<Result<_, _>>()?
+    };
+
+    Ok(defs
+        .into_iter()
+        .map(|def| Loaded {
+            path: path.to_owned(),
+            machine: Machine::from(&def),
+        })
+        .collect())
+}
+
+/// The closest directory containing `Cargo.toml` among the ancestors of the
+/// file.
+fn manifest_dir(path: &Path) -> Option<PathBuf> {
+    let path = path.canonicalize().ok()?;
+    path.ancestors()
+        .skip(1)
+        .find(|dir| dir.join("Cargo.toml").is_file())
+        .map(Path::to_owned)
+}
+
+/// Collects the invocations of the `state_machine` macro.
+#[derive(Default)]
+struct MacroVisitor {
+    macros: Vec<syn::Macro>,
+}
+
+impl<'ast> Visit<'ast> for MacroVisitor {
+    fn visit_macro(&mut self, mac: &'ast syn::Macro) {
+        if mac
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == "state_machine")
+        {
+            self.macros.push(mac.clone());
+        }
+    }
+}
@@ -0,0 +1,209 @@
+//! A command line tool for inspecting state machines defined with the
+//! `rust-fsm` DSL without compiling the crate defining them.
+
+use rust_fsm_dsl_core::{
+    check::{self, Severity},
+    diagram,
+};
+use std::{path::PathBuf, process::ExitCode};
+
+mod load;
+
+const USAGE: &str = "\
+Usage: rust-fsm <COMMAND> [OPTIONS] <FILE>...
+
+Inspect state machines defined with the rust-fsm DSL. FILE is either a `.fsm`
+file containing a single definition or a Rust source file, in which case every
+invocation of the `state_machine` macro is inspected.
+
+Commands:
+  check    Check reachability, determinism and completeness of the machines
+  render   Print the state diagrams of the machines
+  stats    Print the sizes of the machines
+
+Options:
+  --machine <NAME>    Only inspect the machine with the given name
+  --format <FORMAT>   The diagram format for `render`: mermaid (default), dot,
+                      plantuml or scxml
+  --deny-warnings     Make `check` fail on warnings
+  -h, --help          Print this message";
+
+#[derive(Clone, Copy)]
+enum Command {
+    Check,
+    Render,
+    Stats,
+}
+
+#[derive(Clone, Copy)]
+enum Format {
+    Mermaid,
+    Dot,
+    PlantUml,
+    Scxml,
+}
+
+struct Options {
+    command: Command,
+    files: Vec<PathBuf>,
+    machine: Option<String>,
+    format: Format,
+    deny_warnings: bool,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let command = match args.next().as_deref() {
+        Some("check") => Command::Check,
+        Some("render") => Command::Render,
+        Some("stats") => Command::Stats,
+        Some(command) => return Err(format!("unknown command `{command}`")),
+        None => return Err("no command specified".to_string()),
+    };
+    let mut options = Options {
+        command,
+        files: Vec::new(),
+        machine: None,
+        format: Format::Mermaid,
+        deny_warnings: false,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--machine" => {
+                options.machine = Some(args.next().ok_or("`--machine` requires a value")?);
+            }
+            "--format" => {
+                options.format = match args.next().as_deref() {
+                    Some("mermaid") => Format::Mermaid,
+                    Some("dot") => Format::Dot,
+                    Some("plantuml") => Format::PlantUml,
+                    Some("scxml") => Format::Scxml,
+                    Some(format) => return Err(format!("unknown format `{format}`")),
+                    None => return Err("`--format` requires a value".to_string()),
+                }
+            }
+            "--deny-warnings" => options.deny_warnings = true,
+            option if option.starts_with('-') => {
+                return Err(format!("unknown option `{option}`"));
+            }
+            file => options.files.push(file.into()),
+        }
+    }
+    if options.files.is_empty() {
+        return Err("no files specified".to_string());
+    }
+    Ok(options)
+}
+
+fn main() -> ExitCode {
+    let args: Vec<_> = std::env::args().skip(1).collect();
+    if args.is_empty() || args.iter().any(|arg| arg == "-h" || arg == "--help") {
+        println!("{USAGE}");
+        return ExitCode::SUCCESS;
+    }
+    let options = match parse_args(args.into_iter()) {
+        Ok(options) => options,
+        Err(error) => {
+            eprintln!("error: {error}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut machines = Vec::new();
+    for file in &options.files {
+        match load::load(file) {
+            Ok(loaded) => machines.extend(loaded),
+            Err(error) => {
+                eprintln!("error: {error}");
+                return ExitCode::from(2);
+            }
+        }
+    }
+    if let Some(name) = &options.machine {
+        machines.retain(|loaded| loaded.machine.name == *name);
+        if machines.is_empty() {
+            eprintln!("error: machine `{name}` not found");
+            return ExitCode::from(2);
+        }
+    }
+
+    match options.command {
+        Command::Check => check(&machines, options.deny_warnings),
+        Command::Render => render(&machines, options.format),
+        Command::Stats => stats(&machines),
+    }
+}
+
+fn check(machines: &[load::Loaded], deny_warnings: bool) -> ExitCode {
+    let mut errors = 0;
+    let mut warnings = 0;
+    for loaded in machines {
+        for issue in check::check(&loaded.machine) {
+            let severity = match issue.severity() {
+                Severity::Error => {
+                    errors += 1;
+                    "error"
+                }
+                Severity::Warning => {
+                    warnings += 1;
+                    "warning"
+                }
+            };
+            println!(
+                "{}: {}: {severity}: {issue}",
+                loaded.path.display(),
+                loaded.machine.name
+            );
+        }
+    }
+    println!(
+        "checked {} machine(s): {errors} error(s), {warnings} warning(s)",
+        machines.len()
+    );
+    if errors > 0 || (deny_warnings && warnings > 0) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn render(machines: &[load::Loaded], format: Format) -> ExitCode {
+    let diagrams: Vec<_> = machines
+        .iter()
+        .map(|loaded| match format {
+            Format::Mermaid => diagram::mermaid(&loaded.machine),
+            Format::Dot => diagram::dot(&loaded.machine),
+            Format::PlantUml => diagram::plantuml(&loaded.machine),
+            Format::Scxml => diagram::scxml(&loaded.machine),
+        })
+        .collect();
+    println!("{}", diagrams.join("\n\n"));
+    ExitCode::SUCCESS
+}
+
+fn stats(machines: &[load::Loaded]) -> ExitCode {
+    for loaded in machines {
+        let machine = &loaded.machine;
+        let reachable = check::reachable_states(machine)
+            .into_iter()
+            .filter(|&reachable| reachable)
+            .count();
+        let classes = machine
+            .equivalence_classes()
+            .into_iter()
+            .max()
+            .map_or(0, |max| max + 1);
+        let combinations = machine.states.len() * machine.inputs.len();
+        println!("{} ({})", machine.name, loaded.path.display());
+        println!(
+            "  states: {} ({reachable} reachable, {classes} after minimization)",
+            machine.states.len()
+        );
+        println!("  inputs: {}", machine.inputs.len());
+        println!("  outputs: {}", machine.outputs.len());
+        println!(
+            "  transitions: {} of {combinations} possible",
+            machine.transitions.len()
+        );
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,109 @@
+use std::process::{Command, Output};
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_rust-fsm"))
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        // The tool runs outside of cargo.
+        .env_remove("CARGO_MANIFEST_DIR")
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+fn stdout(output: &Output) -> &str {
+    std::str::from_utf8(&output.stdout).unwrap()
+}
+
+#[test]
+fn check_passes() {
+    let output = run(&["check", "tests/machines/door.fsm"]);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "checked 1 machine(s): 0 error(s), 0 warning(s)\n"
+    );
+}
+
+#[test]
+fn check_reports_issues() {
+    let output = run(&["check", "tests/machines/faulty.fsm"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(
+        stdout(&output),
+        "tests/machines/faulty.fsm: faulty: warning: state `Stopped` is unreachable from the initial state\n\
+         tests/machines/faulty.fsm: faulty: error: transition `Running(Stop)` has conflicting definitions, only the first one is used\n\
+         tests/machines/faulty.fsm: faulty: warning: transition `Idle(Start)` is defined more than once\n\
+         tests/machines/faulty.fsm: faulty: warning: state `Idle` does not handle `Stop`\n\
+         tests/machines/faulty.fsm: faulty: warning: state `Running` does not handle `Start`\n\
+         tests/machines/faulty.fsm: faulty: warning: state `Stopped` does not handle `Stop`\n\
+         checked 1 machine(s): 1 error(s), 5 warning(s)\n"
+    );
+}
+
+#[test]
+fn check_denies_warnings() {
+    let output = run(&["check", "tests/machines/machines.rs"]);
+    assert!(output.status.success());
+    let output = run(&["check", "--deny-warnings", "tests/machines/machines.rs"]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn check_included() {
+    // Relative to the including file.
+    let output = run(&["check", "tests/machines/included.fsm"]);
+    assert!(output.status.success());
+    // Relative to the crate, as in the macro.
+    let output = run(&["check", "tests/machines/included.rs"]);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "checked 1 machine(s): 0 error(s), 0 warning(s)\n"
+    );
+}
+
+#[test]
+fn parse_error_location() {
+    let output = run(&["check", "tests/machines/invalid.fsm"]);
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(
+        std::str::from_utf8(&output.stderr).unwrap(),
+        "error: tests/machines/invalid.fsm:4:15: expected `=>`\n"
+    );
+}
+
+#[test]
+fn render() {
+    let output = run(&[
+        "render",
+        "--format",
+        "plantuml",
+        "--machine",
+        "counter",
+        "tests/machines/machines.rs",
+    ]);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "@startuml\n\
+         [*] --> Zero\n\
+         Zero --> One : Increment\n\
+         One --> Zero : Increment / Overflow\n\
+         One --> Zero : Reset\n\
+         @enduml\n"
+    );
+}
+
+#[test]
+fn stats() {
+    let output = run(&["stats", "tests/machines/faulty.fsm"]);
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        "faulty (tests/machines/faulty.fsm)\n  \
+         states: 3 (2 reachable, 2 after minimization)\n  \
+         inputs: 2\n  \
+         outputs: 0\n  \
+         transitions: 5 of 6 possible\n"
+    );
+}
@@ -0,0 +1,3 @@
+use rust_fsm::*;
+
+state_machine!(include = "tests/machines/door.fsm");
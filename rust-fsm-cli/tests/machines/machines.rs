@@ -0,0 +1,20 @@
+use rust_fsm::*;
+
+state_machine! {
+    light(Off)
+
+    Off(Toggle) => On,
+    On(Toggle) => Off,
+}
+
+mod nested {
+    rust_fsm::state_machine! {
+        counter(Zero)
+
+        Zero(Increment) => One,
+        One => {
+            Increment => Zero [Overflow],
+            Reset => Zero
+        }
+    }
+}
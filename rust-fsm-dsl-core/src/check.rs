@@ -0,0 +1,153 @@
+//! Static checks of machine definitions: reachability, determinism and
+//! completeness.
+
+use crate::model::Machine;
+use std::{collections::VecDeque, fmt};
+
+/// How serious an issue is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// A problem found in a machine definition.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    /// The state cannot be reached from the initial state.
+    Unreachable { state: String },
+    /// The same transition is listed more than once.
+    Duplicate { state: String, input: String },
+    /// The state has different transitions on the same input. Only the first
+    /// one is used by the generated code.
+    Nondeterministic { state: String, input: String },
+    /// The state does not handle some of the inputs. States without outgoing
+    /// transitions are considered final and are never reported, neither are
+    /// machines with a default policy.
+    Incomplete { state: String, inputs: Vec<String> },
+}
+
+impl Issue {
+    pub fn severity(&self) -> Severity {
+        match self {
+            Issue::Nondeterministic { .. } => Severity::Error,
+            Issue::Unreachable { .. } | Issue::Duplicate { .. } | Issue::Incomplete { .. } => {
+                Severity::Warning
+            }
+        }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Issue::Unreachable { state } => {
+                write!(f, "state `{state}` is unreachable from the initial state")
+            }
+            Issue::Duplicate { state, input } => {
+                write!(f, "transition `{state}({input})` is defined more than once")
+            }
+            Issue::Nondeterministic { state, input } => write!(
+                f,
+                "transition `{state}({input})` has conflicting definitions, only the first one is used"
+            ),
+            Issue::Incomplete { state, inputs } => {
+                write!(f, "state `{state}` does not handle ")?;
+                for (i, input) in inputs.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "`{input}`")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Run all checks on the machine. Issues are ordered by their kind and then by
+/// the order of the states.
+pub fn check(machine: &Machine) -> Vec<Issue> {
+    let mut issues = Vec::new();
+
+    let reachable = reachable_states(machine);
+    for (state, reachable) in machine.states.iter().zip(reachable) {
+        if !reachable {
+            issues.push(Issue::Unreachable {
+                state: state.clone(),
+            });
+        }
+    }
+
+    for (i, transition) in machine.transitions.iter().enumerate() {
+        let first = machine.transitions[..i].iter().find(|previous| {
+            previous.from == transition.from && previous.input == transition.input
+        });
+        let Some(first) = first else {
+            continue;
+        };
+        let state = transition.from.clone();
+        let input = transition.input.clone();
+        if first.to == transition.to && first.outputs == transition.outputs {
+            issues.push(Issue::Duplicate { state, input });
+        } else {
+            issues.push(Issue::Nondeterministic { state, input });
+        }
+    }
+
+    // Every combination is handled when there is a default policy.
+    let default_policy = machine.options.default.is_some();
+    for state in machine.states.iter().filter(|_| !default_policy) {
+        let handled: Vec<_> = machine
+            .transitions
+            .iter()
+            .filter(|transition| transition.from == *state)
+            .map(|transition| &transition.input)
+            .collect();
+        if handled.is_empty() {
+            continue;
+        }
+        let missing: Vec<_> = machine
+            .inputs
+            .iter()
+            .filter(|input| !handled.contains(input))
+            .cloned()
+            .collect();
+        if !missing.is_empty() {
+            issues.push(Issue::Incomplete {
+                state: state.clone(),
+                inputs: missing,
+            });
+        }
+    }
+
+    issues
+}
+
+/// Find the states reachable from the initial state. Outputs a flag for every
+/// state in `states`.
+///
+/// # Panics
+///
+/// Panics if a transition refers to a state that is not listed in the machine.
+pub fn reachable_states(machine: &Machine) -> Vec<bool> {
+    let index_of = |state: &str| machine.state_index(state).expect("unknown state");
+    let transitions: Vec<_> = machine.effective_transitions().collect();
+    let mut reachable = vec![false; machine.states.len()];
+    let initial_state = index_of(&machine.initial_state);
+    reachable[initial_state] = true;
+    let mut queue = VecDeque::from([initial_state]);
+    while let Some(state) = queue.pop_front() {
+        for transition in &transitions {
+            if transition.from != machine.states[state] {
+                continue;
+            }
+            let next = index_of(&transition.to);
+            if !reachable[next] {
+                reachable[next] = true;
+                queue.push_back(next);
+            }
+        }
+    }
+    reachable
+}
@@ -0,0 +1,1413 @@
+//! Generation of the code of state machines.
+
+use crate::{
+    diagram,
+    model::{Backend, Machine, StateKind},
+    parser::StateMachineDef,
+};
+use proc_macro2::{Literal, Span, TokenStream};
+use quote::{quote, quote_spanned, ToTokens};
+use std::{collections::HashMap, iter::FromIterator};
+use syn::{
+    parse::{Error, Result},
+    punctuated::Punctuated,
+    Attribute, GenericParam, Generics, Ident, Path, Token,
+};
+
+/// The full information about a state transition.
+#[derive(Clone)]
+struct Transition {
+    initial_state: Ident,
+    input_value: Ident,
+    final_state: Ident,
+    outputs: Vec<Ident>,
+    doc: Vec<String>,
+}
+
+fn attrs_to_token_stream(attrs: &[Attribute]) -> TokenStream {
+    let attrs = attrs.iter().map(ToTokens::to_token_stream);
+    TokenStream::from_iter(attrs)
+}
+
+/// Generate the state machine module from the parsed definition. When
+/// `doc_diagrams` is set, the diagrams selected in the definition are included
+/// into the documentation (this requires the `diagram` feature of `rust-fsm`).
+pub fn generate(input: &StateMachineDef, doc_diagrams: bool) -> Result<TokenStream> {
+    let doc = attrs_to_token_stream(&input.doc);
+    let attrs = attrs_to_token_stream(&input.attributes);
+
+    let fsm_name = &input.name;
+    let visibility = &input.visibility;
+
+    let machine = Machine::from(input);
+    if machine.transitions.is_empty() {
+        return Err(Error::new(
+            fsm_name.span(),
+            "rust-fsm: at least one state transition must be provided",
+        ));
+    }
+    if machine.options.complete && machine.options.default.is_none() {
+        let missing: Vec<_> = machine
+            .missing_transitions()
+            .into_iter()
+            .filter(|(state, _)| machine.state_info(state).kind != StateKind::Final)
+            .map(|(state, input)| format!("`{state}({input})`"))
+            .collect();
+        if !missing.is_empty() {
+            return Err(Error::new(
+                fsm_name.span(),
+                format!(
+                    "rust-fsm: the machine is not complete, missing transitions: {}",
+                    missing.join(", ")
+                ),
+            ));
+        }
+    }
+
+    // The names in the model do not carry spans, so the spans are taken from
+    // the definition to point the errors in the generated code at it.
+    let spans = ident_spans(input);
+    let ident = |name: &String| -> Result<Ident> {
+        let span = spans.get(name).copied().unwrap_or_else(Span::call_site);
+        let mut ident: Ident = syn::parse_str(name).map_err(|_| {
+            Error::new(
+                span,
+                format!("rust-fsm: `{name}` is not a valid identifier"),
+            )
+        })?;
+        ident.set_span(span);
+        Ok(ident)
+    };
+    let initial_state = ident(&machine.initial_state)?;
+    let states = machine
+        .states
+        .iter()
+        .map(ident)
+        .collect::<Result<Vec<_>>>()?;
+    let inputs = machine
+        .inputs
+        .iter()
+        .map(ident)
+        .collect::<Result<Vec<_>>>()?;
+    let outputs = machine
+        .outputs
+        .iter()
+        .map(ident)
+        .collect::<Result<Vec<_>>>()?;
+    // The transitions added by the default policy follow the defined ones.
+    // Only the defined transitions give the output of the state they enter.
+    let default_transitions = machine.default_transitions();
+    let transitions = machine
+        .transitions
+        .iter()
+        .map(|transition| (transition, machine.transition_outputs(transition)))
+        .chain(
+            default_transitions
+                .iter()
+                .map(|transition| (transition, transition.outputs.iter().collect())),
+        )
+        .map(|(transition, outputs)| {
+            Ok(Transition {
+                initial_state: ident(&transition.from)?,
+                input_value: ident(&transition.input)?,
+                final_state: ident(&transition.to)?,
+                outputs: outputs.into_iter().map(ident).collect::<Result<_>>()?,
+                doc: transition.doc.clone(),
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let state_outputs = machine
+        .states
+        .iter()
+        .filter_map(|state| {
+            let output = machine.state_info(state).output?;
+            Some(ident(state).and_then(|state| Ok((state, ident(&output)?))))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let table_backend = machine.options.backend == Backend::Table;
+    // `u16::MAX` marks the missing entries in the tables.
+    if table_backend
+        && [&states, &inputs, &outputs]
+            .iter()
+            .any(|v| v.len() >= 0xffff)
+    {
+        return Err(Error::new(
+            fsm_name.span(),
+            "rust-fsm: the table backend supports at most 65534 states, inputs and outputs",
+        ));
+    }
+    // The table backend casts the values of the generated enums to the indices
+    // in the tables. The enums of generic machines are looked up with the
+    // reflection instead, since they have the hidden variant holding data.
+    let repr = if table_backend && input.generics.params.is_empty() {
+        let derives: Vec<_> = ["Clone", "Copy"]
+            .into_iter()
+            .filter(|name| !derives(&input.attributes, name))
+            .map(|name| Ident::new(name, Span::call_site()))
+            .collect();
+        let derive = (!derives.is_empty()).then(|| quote!(#[derive(#(#derives),*)]));
+        quote! {
+            #derive
+            #[repr(u16)]
+        }
+    } else {
+        quote!()
+    };
+
+    let classes = machine.equivalence_classes();
+    let generics = GenericParams::new(&input.generics);
+    let GenericParams {
+        alias: alias_params,
+        args: generic_args,
+        ..
+    } = &generics;
+
+    let custom = CustomTypes {
+        input: input.input_type.is_some(),
+        state: input.state_type.is_some(),
+        output: input.output_type.is_some(),
+    };
+
+    let mergeable_warnings = if machine.options.warn_mergeable {
+        mergeable_warnings(&classes, &states)
+    } else {
+        quote!()
+    };
+
+    let minimized = if machine.options.minimize {
+        minimized_machine(
+            &initial_state,
+            &transitions,
+            &state_outputs,
+            &classes,
+            &states,
+            &inputs,
+            &outputs,
+            custom,
+            machine.options.backend,
+            &generics,
+            &attrs,
+            &repr,
+        )
+    } else {
+        quote!()
+    };
+
+    let typestate = if machine.options.typestate {
+        if generics.phantom.is_some() {
+            return Err(Error::new_spanned(
+                &input.generics,
+                "rust-fsm: the typestate API is not supported for generic machines",
+            ));
+        }
+        let state_docs: Vec<_> = machine
+            .states
+            .iter()
+            .map(|state| machine.state_info(state).doc)
+            .collect();
+        typestate_api(&initial_state, &transitions, &states, &state_docs)?
+    } else {
+        quote!()
+    };
+
+    let (input_type, input_impl) = match &input.input_type {
+        Some(t) => (quote!(#t), quote!()),
+        None => {
+            let variants = inputs.iter().map(ToTokens::to_token_stream).collect();
+            let def = generics.enum_def(quote!(Input), variants);
+            (
+                quote!(Input #generic_args),
+                quote! {
+                    #attrs
+                    #repr
+                    #def
+                },
+            )
+        }
+    };
+
+    let (state_type, state_impl) = match &input.state_type {
+        Some(t) => (quote!(#t), quote!()),
+        None => {
+            let variants = machine
+                .states
+                .iter()
+                .zip(&states)
+                .map(|(name, state)| {
+                    let doc = machine.state_info(name).doc;
+                    quote!(#(#[doc = #doc])* #state)
+                })
+                .collect();
+            let def = generics.enum_def(quote!(State), variants);
+            let atomic = atomic_state(&states, &generics);
+            (
+                quote!(State #generic_args),
+                quote! {
+                    #attrs
+                    #repr
+                    #def
+
+                    #atomic
+                },
+            )
+        }
+    };
+
+    let (output_type, output_impl) = match &input.output_type {
+        Some(t) => (quote!(#t), quote!()),
+        None => {
+            // Many attrs and derives may work incorrectly (or simply not work) for empty enums, so we just skip them
+            // altogether if the output alphabet is empty.
+            let attrs = if outputs.is_empty() {
+                quote!()
+            } else {
+                quote!(#attrs #repr)
+            };
+            let variants = outputs.iter().map(ToTokens::to_token_stream).collect();
+            let def = generics.enum_def(quote!(Output), variants);
+            (
+                quote!(Output #generic_args),
+                quote! {
+                    #attrs
+                    #def
+                },
+            )
+        }
+    };
+
+    let machine_impl = impl_state_machine(
+        AlphabetTypes {
+            input: input_type,
+            state: state_type,
+            output: output_type,
+        },
+        &initial_state,
+        &transitions,
+        &state_outputs,
+        &states,
+        &inputs,
+        &outputs,
+        custom,
+        machine.options.backend,
+        &generics,
+    );
+
+    let dot_diagram = diagram::dot(&machine);
+
+    let plantuml = if machine.options.export_plantuml {
+        let document = diagram::plantuml(&machine);
+        quote! {
+            /// The state diagram of this machine in the PlantUML format.
+            pub const PLANTUML: &str = #document;
+        }
+    } else {
+        quote!()
+    };
+
+    let scxml = if machine.options.export_scxml {
+        let document = diagram::scxml(&machine);
+        quote! {
+            /// The definition of this machine as an SCXML document.
+            pub const SCXML: &str = #document;
+        }
+    } else {
+        quote!()
+    };
+
+    let mermaid_diagram = diagram::mermaid(&machine);
+    let diagram = if doc_diagrams {
+        let mermaid = if machine.options.doc_mermaid {
+            let lines = doc_code_block("mermaid", &mermaid_diagram);
+            quote! {
+                #[cfg_attr(doc, ::rust_fsm::aquamarine)]
+                #lines
+            }
+        } else {
+            quote!()
+        };
+        let dot = if machine.options.doc_dot {
+            doc_code_block("dot", &dot_diagram)
+        } else {
+            quote!()
+        };
+        quote!(#mermaid #dot)
+    } else {
+        quote!()
+    };
+
+    // Make the compiler track the changes in the source files.
+    let source_files = input.source_files.iter().map(|path| {
+        quote! {
+            const _: &[u8] = include_bytes!(#path);
+        }
+    });
+
+    let impl_def = generics.impl_def();
+    let output = quote! {
+        #doc
+        #diagram
+        #visibility mod #fsm_name {
+            // The paths in the definition are relative to the scope of the
+            // macro invocation.
+            #[allow(unused_imports)]
+            use super::*;
+
+            #attrs
+            #impl_def
+
+            pub type StateMachine #alias_params = ::rust_fsm::StateMachine<Impl #generic_args>;
+
+            /// The state diagram of this machine in the Mermaid format.
+            pub const MERMAID: &str = #mermaid_diagram;
+            /// The state diagram of this machine in the Graphviz DOT format.
+            pub const DOT: &str = #dot_diagram;
+            #plantuml
+            #scxml
+
+            #input_impl
+            #state_impl
+            #output_impl
+
+            #machine_impl
+
+            #mergeable_warnings
+            #minimized
+            #typestate
+            #(#source_files)*
+        }
+    };
+
+    Ok(output)
+}
+
+/// Whether the attributes derive the trait with the given name.
+fn derives(attributes: &[Attribute], name: &str) -> bool {
+    attributes
+        .iter()
+        .filter(|attribute| attribute.path().is_ident("derive"))
+        .any(|attribute| {
+            attribute
+                .parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated)
+                .is_ok_and(|paths| {
+                    paths
+                        .iter()
+                        .any(|path| path.segments.last().is_some_and(|s| s.ident == name))
+                })
+        })
+}
+
+/// Collect the spans of the identifiers in the definition. The first
+/// occurrence of every name wins.
+fn ident_spans(def: &StateMachineDef) -> HashMap<String, Span> {
+    let mut spans = HashMap::new();
+    let mut add = |ident: &Ident| {
+        spans
+            .entry(ident.to_string())
+            .or_insert_with(|| ident.span());
+    };
+    add(&def.initial_state);
+    for transition_def in &def.transitions {
+        add(&transition_def.initial_state);
+        if let Some(output) = &transition_def.state_output {
+            add(output);
+        }
+        for entry in &transition_def.transitions {
+            add(&entry.input_value);
+            add(&entry.final_state);
+            for output in &entry.outputs {
+                add(output);
+            }
+        }
+    }
+    spans
+}
+
+/// Turn the text into a fenced code block in the doc comments.
+fn doc_code_block(language: &str, text: &str) -> TokenStream {
+    let lines = std::iter::once(format!("```{language}"))
+        .chain(text.lines().map(String::from))
+        .chain(std::iter::once("```".to_string()));
+    quote!(#(#[doc = #lines])*)
+}
+
+/// Which of the alphabets are supplied by the user instead of being generated.
+/// Custom types may have variants unknown to the macro.
+#[derive(Clone, Copy)]
+struct CustomTypes {
+    input: bool,
+    state: bool,
+    output: bool,
+}
+
+/// The types used as the alphabets and the set of states of a machine.
+struct AlphabetTypes {
+    input: TokenStream,
+    state: TokenStream,
+    output: TokenStream,
+}
+
+/// The path to a variant of one of the types. It is spanned at the name of the
+/// variant in the definition and has the same tokens at every use, so that a
+/// variant missing from a custom type is reported once, at its name.
+fn variant(ty: &TokenStream, variant: &Ident) -> TokenStream {
+    quote_spanned!(variant.span()=> <#ty>::#variant)
+}
+
+/// The generic parameters of a machine in the forms used by the generated
+/// items. All of them are empty for machines without generic parameters.
+struct GenericParams {
+    /// The parameters with their bounds for declaring types.
+    decl: TokenStream,
+    /// The parameters for `impl` blocks.
+    impl_params: TokenStream,
+    /// The parameters as the arguments of the generated types.
+    args: TokenStream,
+    where_clause: TokenStream,
+    /// The parameters without bounds for declaring type aliases, where the
+    /// bounds are not enforced.
+    alias: TokenStream,
+    /// The `PhantomData` using all lifetimes and type parameters, `None` for
+    /// machines without generic parameters.
+    phantom: Option<TokenStream>,
+}
+
+impl GenericParams {
+    fn new(generics: &Generics) -> Self {
+        let (impl_params, args, where_clause) = generics.split_for_impl();
+        let mut alias = generics.clone();
+        alias.where_clause = None;
+        let mut phantom = Vec::new();
+        for param in &mut alias.params {
+            match param {
+                GenericParam::Lifetime(param) => {
+                    param.colon_token = None;
+                    param.bounds.clear();
+                    let lifetime = &param.lifetime;
+                    phantom.push(quote!(&#lifetime ()));
+                }
+                GenericParam::Type(param) => {
+                    param.colon_token = None;
+                    param.bounds.clear();
+                    let ident = &param.ident;
+                    phantom.push(quote!(fn() -> #ident));
+                }
+                GenericParam::Const(_) => {}
+            }
+        }
+        let phantom = (!generics.params.is_empty())
+            .then(|| quote!(::core::marker::PhantomData<(#(#phantom,)*)>));
+        Self {
+            decl: generics.to_token_stream(),
+            impl_params: impl_params.to_token_stream(),
+            args: args.to_token_stream(),
+            where_clause: where_clause.to_token_stream(),
+            alias: alias.to_token_stream(),
+            phantom,
+        }
+    }
+
+    /// Declare an enum with the given variants. The enums of generic machines
+    /// get the hidden `__Phantom` variant using the parameters. It is
+    /// uninhabited, so it is never constructed and may be left out of the
+    /// matches by value.
+    fn enum_def(&self, name: TokenStream, variants: Vec<TokenStream>) -> TokenStream {
+        let Self {
+            decl, where_clause, ..
+        } = self;
+        let phantom = self.phantom.as_ref().map(|phantom| {
+            quote! {
+                #[doc(hidden)]
+                __Phantom(::core::convert::Infallible, #phantom)
+            }
+        });
+        let variants = variants.into_iter().chain(phantom);
+        quote! {
+            pub enum #name #decl #where_clause {
+                #(#variants),*
+            }
+        }
+    }
+
+    /// Declare the `Impl` type: a unit struct, or a struct holding the
+    /// `PhantomData` for generic machines.
+    fn impl_def(&self) -> TokenStream {
+        let Self {
+            decl, where_clause, ..
+        } = self;
+        match &self.phantom {
+            Some(phantom) => quote!(pub struct Impl #decl (#phantom) #where_clause;),
+            None => quote!(
+                pub struct Impl;
+            ),
+        }
+    }
+}
+
+/// Generate the implementations of `StateMachineImpl` and `StateMachineReflect`
+/// for `Impl`. `state_outputs` pairs the states with their own outputs.
+#[allow(clippy::too_many_arguments)]
+fn impl_state_machine(
+    types: AlphabetTypes,
+    initial_state: &Ident,
+    transitions: &[Transition],
+    state_outputs: &[(Ident, Ident)],
+    states: &[Ident],
+    inputs: &[Ident],
+    outputs: &[Ident],
+    custom: CustomTypes,
+    backend: Backend,
+    generics: &GenericParams,
+) -> TokenStream {
+    // The hidden variant of the generated enums needs a fallback in the
+    // matches by reference.
+    let generic = generics.phantom.is_some();
+    let reflect_state = reflect_alphabet(
+        quote!(state),
+        quote!(State),
+        &types.state,
+        states,
+        custom.state || generic,
+    );
+    let reflect_input = reflect_alphabet(
+        quote!(input),
+        quote!(Input),
+        &types.input,
+        inputs,
+        custom.input || generic,
+    );
+    let reflect_output = reflect_alphabet(
+        quote!(output),
+        quote!(Output),
+        &types.output,
+        outputs,
+        custom.output || generic,
+    );
+
+    let (transition, output, nth_output) = match backend {
+        Backend::Match => match_functions(&types, transitions),
+        Backend::Table => table_functions(
+            &types,
+            transitions,
+            states,
+            inputs,
+            outputs,
+            CustomTypes {
+                input: custom.input || generic,
+                state: custom.state || generic,
+                output: custom.output || generic,
+            },
+        ),
+    };
+    // The functions are `const` unless they depend on the types unknown to the
+    // macro or on the generic parameters. The inherent functions take
+    // precedence over the ones of the trait.
+    let constant = !(custom.input || custom.state || custom.output || generic);
+    let (const_functions, functions) = if constant {
+        let AlphabetTypes {
+            input: input_type,
+            state: state_type,
+            output: output_type,
+        } = &types;
+        (
+            quote! {
+                impl Impl {
+                    /// The transition function of this machine usable in
+                    /// `const` contexts, see `StateMachineImpl::transition`.
+                    pub const fn transition(
+                        state: &#state_type,
+                        input: &#input_type,
+                    ) -> ::core::option::Option<#state_type> {
+                        #transition
+                    }
+
+                    /// The output function of this machine usable in `const`
+                    /// contexts, see `StateMachineImpl::output`.
+                    pub const fn output(
+                        state: &#state_type,
+                        input: &#input_type,
+                    ) -> ::core::option::Option<#output_type> {
+                        #output
+                    }
+                }
+            },
+            quote! {
+                fn transition(state: &Self::State, input: &Self::Input) -> ::core::option::Option<Self::State> {
+                    Self::transition(state, input)
+                }
+
+                fn output(state: &Self::State, input: &Self::Input) -> ::core::option::Option<Self::Output> {
+                    Self::output(state, input)
+                }
+
+                #nth_output
+            },
+        )
+    } else {
+        (
+            quote!(),
+            quote! {
+                fn transition(state: &Self::State, input: &Self::Input) -> ::core::option::Option<Self::State> {
+                    #transition
+                }
+
+                fn output(state: &Self::State, input: &Self::Input) -> ::core::option::Option<Self::Output> {
+                    #output
+                }
+
+                #nth_output
+            },
+        )
+    };
+
+    let state_output = if state_outputs.is_empty() {
+        quote!()
+    } else {
+        let cases = state_outputs.iter().map(|(state, output_value)| {
+            let state = variant(&types.state, state);
+            let output_value = variant(&types.output, output_value);
+            quote! {
+                #state => ::core::option::Option::Some(#output_value),
+            }
+        });
+        quote! {
+            fn state_output(state: &Self::State) -> ::core::option::Option<Self::Output> {
+                match state {
+                    #(#cases)*
+                    #[allow(unreachable_patterns)]
+                    _ => ::core::option::Option::None,
+                }
+            }
+        }
+    };
+
+    let initial_state = variant(&types.state, initial_state);
+    let AlphabetTypes {
+        input: input_type,
+        state: state_type,
+        output: output_type,
+    } = types;
+    let GenericParams {
+        impl_params,
+        args,
+        where_clause,
+        ..
+    } = generics;
+
+    quote! {
+        #const_functions
+
+        impl #impl_params ::rust_fsm::StateMachineImpl for Impl #args #where_clause {
+            type Input = #input_type;
+            type State = #state_type;
+            type Output = #output_type;
+            const INITIAL_STATE: Self::State = #initial_state;
+
+            #functions
+            #state_output
+        }
+
+        impl #impl_params ::rust_fsm::StateMachineReflect for Impl #args #where_clause {
+            #reflect_state
+            #reflect_input
+            #reflect_output
+        }
+    }
+}
+
+/// Generate the bodies of the transition and output functions matching on the
+/// combination of a state and an input, and the function giving all outputs of
+/// a transition.
+fn match_functions(
+    types: &AlphabetTypes,
+    transitions: &[Transition],
+) -> (TokenStream, TokenStream, TokenStream) {
+    let AlphabetTypes {
+        input: input_type,
+        state: state_type,
+        output: output_type,
+    } = types;
+
+    let transition_cases = transitions.iter().map(|transition| {
+        let initial_state = variant(state_type, &transition.initial_state);
+        let input_value = variant(input_type, &transition.input_value);
+        let final_state = variant(state_type, &transition.final_state);
+        quote! {
+            (#initial_state, #input_value) => {
+                ::core::option::Option::Some(#final_state)
+            }
+        }
+    });
+
+    let output_cases = transitions.iter().filter_map(|transition| {
+        transition.outputs.first().map(|output_value| {
+            let initial_state = variant(state_type, &transition.initial_state);
+            let input_value = variant(input_type, &transition.input_value);
+            let output_value = variant(output_type, output_value);
+            quote! {
+                (#initial_state, #input_value) => {
+                    ::core::option::Option::Some(#output_value)
+                }
+            }
+        })
+    });
+
+    // The default implementation of `nth_output` is enough unless some of the
+    // transitions give more than one output.
+    let nth_output = if transitions.iter().any(|t| t.outputs.len() > 1) {
+        let cases = transitions.iter().flat_map(|transition| {
+            let initial_state = variant(state_type, &transition.initial_state);
+            let input_value = variant(input_type, &transition.input_value);
+            transition
+                .outputs
+                .iter()
+                .enumerate()
+                .map(move |(index, output_value)| {
+                    let output_value = variant(output_type, output_value);
+                    quote! {
+                        (#initial_state, #input_value, #index) => ::core::option::Option::Some(#output_value),
+                    }
+                })
+        });
+        quote! {
+            fn nth_output(
+                state: &Self::State,
+                input: &Self::Input,
+                index: usize,
+            ) -> ::core::option::Option<Self::Output> {
+                match (state, input, index) {
+                    #(#cases)*
+                    _ => ::core::option::Option::None,
+                }
+            }
+        }
+    } else {
+        quote!()
+    };
+
+    (
+        quote! {
+            match (state, input) {
+                #(#transition_cases)*
+                #[allow(unreachable_patterns)]
+                _ => ::core::option::Option::None,
+            }
+        },
+        quote! {
+            match (state, input) {
+                #(#output_cases)*
+                #[allow(unreachable_patterns)]
+                _ => ::core::option::Option::None,
+            }
+        },
+        nth_output,
+    )
+}
+
+/// Generate the bodies of the transition and output functions looking up the
+/// indices of the next state and the outputs in constant tables indexed by the
+/// indices of the state and the input, and the function giving all outputs of
+/// a transition. `u16::MAX` stands for no state or output.
+///
+/// The generated enums are `Copy` and `#[repr(u16)]`, so their values are cast
+/// to the indices and read back from arrays. The tables are references in
+/// `const` items rather than statics, which keeps the lookups `const` without
+/// copying the tables. The alphabets listed in `reflected` (the custom types and the
+/// enums of generic machines) are converted with the reflection instead.
+fn table_functions(
+    types: &AlphabetTypes,
+    transitions: &[Transition],
+    states: &[Ident],
+    inputs: &[Ident],
+    outputs: &[Ident],
+    reflected: CustomTypes,
+) -> (TokenStream, TokenStream, TokenStream) {
+    let position = |idents: &[Ident], ident: &Ident| {
+        let index = idents.iter().position(|i| i == ident).unwrap();
+        u16::try_from(index).unwrap()
+    };
+    let mut next_states = vec![vec![u16::MAX; inputs.len()]; states.len()];
+    let mut transition_outputs = vec![vec![Vec::new(); inputs.len()]; states.len()];
+    let mut defined = vec![vec![false; inputs.len()]; states.len()];
+    // The first transition of every combination wins, like in a `match`.
+    for transition in transitions {
+        let state = usize::from(position(states, &transition.initial_state));
+        let input = usize::from(position(inputs, &transition.input_value));
+        if std::mem::replace(&mut defined[state][input], true) {
+            continue;
+        }
+        next_states[state][input] = position(states, &transition.final_state);
+        transition_outputs[state][input] = transition
+            .outputs
+            .iter()
+            .map(|output| position(outputs, output))
+            .collect();
+    }
+
+    let num_states = states.len();
+    let num_inputs = inputs.len();
+    let table = |rows: Vec<Vec<TokenStream>>| {
+        let rows = rows.into_iter().map(|row| quote!([#(#row),*]));
+        quote!([#(#rows),*])
+    };
+    let literal = |index: u16| Literal::u16_unsuffixed(index).into_token_stream();
+    let transitions_table = table(
+        next_states
+            .iter()
+            .map(|row| row.iter().copied().map(literal).collect())
+            .collect(),
+    );
+    let outputs_table = table(
+        transition_outputs
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|outputs| literal(outputs.first().copied().unwrap_or(u16::MAX)))
+                    .collect()
+            })
+            .collect(),
+    );
+
+    let state_index = table_index(quote!(state), reflected.state);
+    let input_index = table_index(quote!(input), reflected.input);
+    let state_at = |index| table_value(quote!(state), &types.state, states, reflected.state, index);
+    let output_at = |index| {
+        table_value(
+            quote!(output),
+            &types.output,
+            outputs,
+            reflected.output,
+            index,
+        )
+    };
+
+    let nth_output = if transition_outputs.iter().flatten().any(|o| o.len() > 1) {
+        let lists = table(
+            transition_outputs
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|outputs| {
+                            let outputs = outputs.iter().copied().map(literal);
+                            quote!(&[#(#outputs),*])
+                        })
+                        .collect()
+                })
+                .collect(),
+        );
+        let output = output_at(quote!(output));
+        quote! {
+            fn nth_output(
+                state: &Self::State,
+                input: &Self::Input,
+                index: usize,
+            ) -> ::core::option::Option<Self::Output> {
+                const OUTPUT_LISTS: &[[&[u16]; #num_inputs]; #num_states] = &#lists;
+                let output = *OUTPUT_LISTS[#state_index][#input_index].get(index)?;
+                #output
+            }
+        }
+    } else {
+        quote!()
+    };
+
+    let next_state = state_at(quote!(TRANSITIONS[#state_index][#input_index]));
+    let output = output_at(quote!(OUTPUTS[#state_index][#input_index]));
+    (
+        quote! {
+            const TRANSITIONS: &[[u16; #num_inputs]; #num_states] = &#transitions_table;
+            #next_state
+        },
+        quote! {
+            const OUTPUTS: &[[u16; #num_inputs]; #num_states] = &#outputs_table;
+            #output
+        },
+        nth_output,
+    )
+}
+
+/// The index of the value of `state` or `input` in the tables, see
+/// [`table_functions`].
+fn table_index(value: TokenStream, reflected: bool) -> TokenStream {
+    if reflected {
+        let index_fn = Ident::new(&format!("{value}_index"), Span::call_site());
+        quote!(<Self as ::rust_fsm::StateMachineReflect>::#index_fn(#value)?)
+    } else {
+        quote!(*#value as u16 as usize)
+    }
+}
+
+/// The optional value at the given `u16` index read from the tables, see
+/// [`table_functions`].
+fn table_value(
+    name: TokenStream,
+    ty: &TokenStream,
+    variants: &[Ident],
+    reflected: bool,
+    index: TokenStream,
+) -> TokenStream {
+    if reflected {
+        // The reflection rejects `u16::MAX`, which is out of range.
+        let at_fn = Ident::new(&format!("{name}_at"), Span::call_site());
+        return quote!(<Self as ::rust_fsm::StateMachineReflect>::#at_fn(usize::from(#index)));
+    }
+    if variants.is_empty() {
+        return quote! {{
+            let _ = #index;
+            ::core::option::Option::None
+        }};
+    }
+    let num_variants = variants.len();
+    let variants = variants.iter().map(|value| variant(ty, value));
+    quote! {{
+        const VALUES: &[#ty; #num_variants] = &[#(#variants),*];
+        match #index {
+            u16::MAX => ::core::option::Option::None,
+            index => ::core::option::Option::Some(VALUES[index as usize]),
+        }
+    }}
+}
+
+/// Generate the members of the `StateMachineReflect` implementation for one
+/// of the alphabets (or the set of states).
+fn reflect_alphabet(
+    name: TokenStream,
+    ty: TokenStream,
+    path: &TokenStream,
+    variants: &[Ident],
+    custom: bool,
+) -> TokenStream {
+    let name = name.to_string();
+    let names_const = Ident::new(&format!("{}_NAMES", name.to_uppercase()), Span::call_site());
+    let at_fn = Ident::new(&format!("{name}_at"), Span::call_site());
+    let index_fn = Ident::new(&format!("{name}_index"), Span::call_site());
+    let value = Ident::new(&name, Span::call_site());
+
+    let names = variants.iter().map(|variant| variant.to_string());
+    let variants: Vec<_> = variants.iter().map(|value| variant(path, value)).collect();
+    let indices = 0..variants.len();
+    let at_cases = quote! {
+        #(#indices => ::core::option::Option::Some(#variants),)*
+    };
+    let indices = 0..variants.len();
+    let fallback = if custom {
+        quote! {
+            #[allow(unreachable_patterns)]
+            _ => ::core::option::Option::None,
+        }
+    } else {
+        quote!()
+    };
+
+    quote! {
+        const #names_const: &'static [&'static str] = &[#(#names),*];
+
+        fn #at_fn(index: usize) -> ::core::option::Option<Self::#ty> {
+            match index {
+                #at_cases
+                _ => ::core::option::Option::None,
+            }
+        }
+
+        fn #index_fn(#value: &Self::#ty) -> ::core::option::Option<usize> {
+            match *#value {
+                #(#variants => ::core::option::Option::Some(#indices),)*
+                #fallback
+            }
+        }
+    }
+}
+
+/// Group states by their equivalence classes, leaving only the classes that
+/// have more than one state.
+fn mergeable_groups<'a>(classes: &[usize], states: &'a [Ident]) -> Vec<Vec<&'a Ident>> {
+    let num_classes = classes.iter().max().map_or(0, |max| max + 1);
+    let mut groups = vec![Vec::new(); num_classes];
+    for (state, &class) in states.iter().zip(classes) {
+        groups[class].push(state);
+    }
+    groups.retain(|group| group.len() > 1);
+    groups
+}
+
+/// Produce a compile warning for every group of equivalent states. Procedural
+/// macros cannot emit warnings directly, so this relies on the use of a
+/// deprecated item.
+fn mergeable_warnings(classes: &[usize], states: &[Ident]) -> TokenStream {
+    let warnings = mergeable_groups(classes, states).into_iter().map(|group| {
+        let names = group
+            .iter()
+            .map(|state| format!("`{state}`"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let note =
+            format!("rust-fsm: states {names} are equivalent and can be merged into one state");
+        let span = group[0].span();
+        let item = Ident::new("MergeableStates", span);
+        quote_spanned! {span=>
+            const _: () = {
+                #[deprecated(note = #note)]
+                struct #item;
+                let _ = #item;
+            };
+        }
+    });
+    quote!(#(#warnings)*)
+}
+
+/// Generate the `minimized` module containing the machine with all equivalent
+/// states merged.
+#[allow(clippy::too_many_arguments)]
+fn minimized_machine(
+    initial_state: &Ident,
+    transitions: &[Transition],
+    state_outputs: &[(Ident, Ident)],
+    classes: &[usize],
+    states: &[Ident],
+    inputs: &[Ident],
+    outputs: &[Ident],
+    custom: CustomTypes,
+    backend: Backend,
+    generics: &GenericParams,
+    attrs: &TokenStream,
+    repr: &TokenStream,
+) -> TokenStream {
+    let GenericParams {
+        impl_params,
+        args,
+        where_clause,
+        alias,
+        ..
+    } = generics;
+    let class_of = |state: &Ident| classes[states.iter().position(|s| s == state).unwrap()];
+
+    // Each class is represented by the initial state if it belongs to the
+    // class or by its first member otherwise.
+    let num_classes = classes.iter().max().map_or(0, |max| max + 1);
+    let mut representatives = vec![None; num_classes];
+    representatives[class_of(initial_state)] = Some(initial_state);
+    for (state, &class) in states.iter().zip(classes) {
+        representatives[class].get_or_insert(state);
+    }
+    let representatives: Vec<Ident> = representatives.into_iter().flatten().cloned().collect();
+    let representative_of = |state: &Ident| &representatives[class_of(state)];
+
+    let minimized_transitions: Vec<_> = transitions
+        .iter()
+        .filter(|transition| {
+            *representative_of(&transition.initial_state) == transition.initial_state
+        })
+        .map(|transition| Transition {
+            final_state: representative_of(&transition.final_state).clone(),
+            ..transition.clone()
+        })
+        .collect();
+    let minimized_state_outputs: Vec<_> = state_outputs
+        .iter()
+        .filter(|(state, _)| representative_of(state) == state)
+        .cloned()
+        .collect();
+
+    // The custom types are referred to by the aliases, since relative paths
+    // resolve differently in this module.
+    let machine_impl = impl_state_machine(
+        AlphabetTypes {
+            input: quote!(Input #args),
+            state: quote!(State #args),
+            output: quote!(Output #args),
+        },
+        initial_state,
+        &minimized_transitions,
+        &minimized_state_outputs,
+        &representatives,
+        inputs,
+        outputs,
+        CustomTypes {
+            state: false,
+            ..custom
+        },
+        backend,
+        generics,
+    );
+
+    let variants = representatives
+        .iter()
+        .map(ToTokens::to_token_stream)
+        .collect();
+    let state_def = generics.enum_def(quote!(State), variants);
+    let impl_def = generics.impl_def();
+    let atomic = atomic_state(&representatives, generics);
+    let state_cases = states.iter().map(|state| {
+        let representative = representative_of(state);
+        let state = variant(&quote!(OriginalState #args), state);
+        quote!(#state => ::core::option::Option::Some(State::#representative),)
+    });
+    let fallback = if custom.state || generics.phantom.is_some() {
+        quote! {
+            #[allow(unreachable_patterns)]
+            _ => ::core::option::Option::None,
+        }
+    } else {
+        quote!()
+    };
+
+    quote! {
+        /// The minimized version of this state machine: all equivalent states
+        /// are merged into one state.
+        pub mod minimized {
+            #[allow(unused_imports)]
+            use super::*;
+
+            #attrs
+            #impl_def
+
+            pub type StateMachine #alias = ::rust_fsm::StateMachine<Impl #args>;
+
+            pub type Input #alias = <super::Impl #args as ::rust_fsm::StateMachineImpl>::Input;
+            pub type Output #alias =
+                <super::Impl #args as ::rust_fsm::StateMachineImpl>::Output;
+            type OriginalState #alias =
+                <super::Impl #args as ::rust_fsm::StateMachineImpl>::State;
+
+            #attrs
+            #repr
+            #state_def
+
+            #atomic
+
+            #machine_impl
+
+            /// Maps a state of the original machine to the state of the
+            /// minimized machine it was merged into.
+            pub fn map_state #impl_params (
+                state: &OriginalState #args,
+            ) -> ::core::option::Option<State #args> #where_clause {
+                match *state {
+                    #(#state_cases)*
+                    #fallback
+                }
+            }
+        }
+    }
+}
+
+/// Generate the lossless conversions of the generated `State` enum to and from
+/// integers, so that it can be used in `AtomicStateMachine`. The state is
+/// stored in an `AtomicU8` unless there are more than 256 states.
+fn atomic_state(states: &[Ident], generics: &GenericParams) -> TokenStream {
+    let (width, atomic, bits) = if states.len() <= 256 {
+        ("8", quote!(AtomicU8), quote!(u8))
+    } else {
+        ("32", quote!(AtomicU32), quote!(u32))
+    };
+    let values: Vec<_> = (0..states.len()).map(Literal::usize_unsuffixed).collect();
+    let GenericParams {
+        impl_params,
+        args,
+        where_clause,
+        alias,
+        phantom,
+        ..
+    } = generics;
+    let phantom_case = phantom
+        .as_ref()
+        .map(|_| quote!(Self::__Phantom(never, _) => match never {},));
+    quote! {
+        #[cfg(target_has_atomic = #width)]
+        impl #impl_params ::rust_fsm::AtomicState for State #args #where_clause {
+            type Atomic = ::core::sync::atomic::#atomic;
+
+            fn to_bits(&self) -> #bits {
+                match *self {
+                    #(Self::#states => #values,)*
+                    #phantom_case
+                }
+            }
+
+            fn from_bits(bits: #bits) -> ::core::option::Option<Self> {
+                match bits {
+                    #(#values => ::core::option::Option::Some(Self::#states),)*
+                    _ => ::core::option::Option::None,
+                }
+            }
+        }
+
+        /// The state machine storing its state in an atomic integer.
+        #[cfg(target_has_atomic = #width)]
+        pub type AtomicStateMachine #alias = ::rust_fsm::AtomicStateMachine<Impl #args>;
+    }
+}
+
+/// Generate the `typestate` module: a zero-sized type for every state and the
+/// `Machine` wrapper with a method consuming it for every transition, so that
+/// impossible transitions do not compile. Only the first transition is used for
+/// every combination of a state and an input, like in the transition function.
+fn typestate_api(
+    initial_state: &Ident,
+    transitions: &[Transition],
+    states: &[Ident],
+    state_docs: &[Vec<String>],
+) -> Result<TokenStream> {
+    for state in states {
+        if ["Machine", "TypeState", "sealed"].contains(&state.to_string().as_str()) {
+            return Err(Error::new(
+                state.span(),
+                format!("rust-fsm: the state `{state}` clashes with an item of the typestate API"),
+            ));
+        }
+    }
+
+    let state_types = states.iter().zip(state_docs).map(|(state, doc)| {
+        quote! {
+            #(#[doc = #doc])*
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+            pub struct #state;
+
+            impl sealed::Sealed for #state {}
+
+            impl TypeState for #state {
+                const STATE: <super::Impl as ::rust_fsm::StateMachineImpl>::State =
+                    <super::Impl as ::rust_fsm::StateMachineImpl>::State::#state;
+            }
+        }
+    });
+
+    let mut seen = Vec::new();
+    let mut methods = Vec::new();
+    for transition in transitions {
+        let Transition {
+            initial_state: from,
+            input_value,
+            final_state,
+            outputs,
+            doc,
+        } = transition;
+        if seen.contains(&(from, input_value)) {
+            continue;
+        }
+        seen.push((from, input_value));
+
+        let name = snake_case(&input_value.to_string());
+        if from == initial_state && name == "new" {
+            return Err(Error::new(
+                input_value.span(),
+                format!(
+                    "rust-fsm: the input `{input_value}` of the initial state clashes with \
+                     `Machine::new` of the typestate API"
+                ),
+            ));
+        }
+        let method = match syn::parse_str::<Ident>(&name) {
+            Ok(_) => Ident::new(&name, input_value.span()),
+            Err(_) if !["self", "super", "crate"].contains(&name.as_str()) => {
+                Ident::new_raw(&name, input_value.span())
+            }
+            Err(_) => {
+                return Err(Error::new(
+                    input_value.span(),
+                    format!(
+                        "rust-fsm: `{name}` cannot be the name of a method of the typestate API"
+                    ),
+                ))
+            }
+        };
+
+        let next = quote!(Machine {
+            _state: PhantomData
+        });
+        let output_values = outputs.iter().map(|output_value| {
+            quote!(<super::Impl as ::rust_fsm::StateMachineImpl>::Output::#output_value)
+        });
+        let output_type = quote!(<super::Impl as ::rust_fsm::StateMachineImpl>::Output);
+        let (return_type, body) = match outputs.len() {
+            0 => (quote!(Machine<#final_state>), next),
+            1 => (
+                quote!((Machine<#final_state>, #output_type)),
+                quote!((#next, #(#output_values)*)),
+            ),
+            len => (
+                quote!((Machine<#final_state>, [#output_type; #len])),
+                quote!((#next, [#(#output_values),*])),
+            ),
+        };
+        methods.push(quote! {
+            impl Machine<#from> {
+                #(#[doc = #doc])*
+                pub fn #method(self) -> #return_type {
+                    #body
+                }
+            }
+        });
+    }
+
+    Ok(quote! {
+        /// The typestate API of this state machine: every state is a type and
+        /// every transition is a method of `Machine` in the state it starts
+        /// from, so that impossible transitions are compile errors.
+        pub mod typestate {
+            use ::core::marker::PhantomData;
+
+            mod sealed {
+                pub trait Sealed {}
+            }
+
+            /// A state of the machine represented as a type.
+            pub trait TypeState: sealed::Sealed {
+                /// The state of the enum-based machine this type stands for.
+                const STATE: <super::Impl as ::rust_fsm::StateMachineImpl>::State;
+            }
+
+            #(#state_types)*
+
+            /// The state machine in the state `S`. The transitions consume the
+            /// machine and give it back in the new state along with the
+            /// outputs, if any.
+            #[derive(Debug)]
+            pub struct Machine<S: TypeState> {
+                _state: PhantomData<S>,
+            }
+
+            impl Machine<#initial_state> {
+                /// Create the machine in the initial state.
+                pub fn new() -> Self {
+                    Machine { _state: PhantomData }
+                }
+            }
+
+            impl Default for Machine<#initial_state> {
+                fn default() -> Self {
+                    Self::new()
+                }
+            }
+
+            impl<S: TypeState> From<Machine<S>> for ::rust_fsm::StateMachine<super::Impl> {
+                fn from(_: Machine<S>) -> Self {
+                    Self::from_state(S::STATE)
+                }
+            }
+
+            #(#methods)*
+        }
+    })
+}
+
+/// Convert a name in `UpperCamelCase` to `snake_case`. A run of capitals is
+/// treated as one word, so `HTTPRequest` becomes `http_request`.
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut result = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let previous = chars[i - 1];
+            let next_is_lower = chars.get(i + 1).is_some_and(|c| c.is_lowercase());
+            if previous.is_lowercase()
+                || previous.is_ascii_digit()
+                || (previous.is_uppercase() && next_is_lower)
+            {
+                result.push('_');
+            }
+        }
+        result.extend(c.to_lowercase());
+    }
+    result
+}
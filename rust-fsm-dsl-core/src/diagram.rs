@@ -0,0 +1,281 @@
+//! Generation of state diagrams from the machine definition.
+
+use crate::model::{Machine, StateKind, Transition};
+use std::fmt::Write;
+
+/// Generate a Mermaid `stateDiagram-v2` diagram.
+///
+/// Grouped states are drawn inside composite states along with the transitions
+/// between them. The documentation of the states and their outgoing transitions
+/// is attached as notes, the outputs of the states are shown as
+/// `entry / Output` inside them. Final states are connected to the final
+/// pseudostate, final and error states are styled with the `finalState` and
+/// `errorState` classes.
+pub fn mermaid(machine: &Machine) -> String {
+    let mut diagram = String::from("stateDiagram-v2\n");
+    let transitions = machine.diagram_transitions();
+    let group_of = |state: &str| machine.state_info(state).group;
+    let groups = machine.groups();
+
+    // Composite states must be declared before their states are referred to.
+    for (group, states) in &groups {
+        writeln!(diagram, "    state {group} {{").unwrap();
+        for state in states {
+            match machine.state_info(state).output {
+                Some(output) => writeln!(diagram, "        {state}: entry / {output}"),
+                None => writeln!(diagram, "        {state}"),
+            }
+            .unwrap();
+        }
+        for transition in &transitions {
+            if group_of(&transition.from).as_deref() == Some(group)
+                && group_of(&transition.to).as_deref() == Some(group)
+            {
+                mermaid_transition(&mut diagram, "        ", transition);
+            }
+        }
+        for state in states {
+            mermaid_note(&mut diagram, "        ", machine, state);
+        }
+        diagram.push_str("    }\n");
+    }
+
+    for state in &machine.states {
+        let info = machine.state_info(state);
+        if let (None, Some(output)) = (info.group, info.output) {
+            writeln!(diagram, "    {state}: entry / {output}").unwrap();
+        }
+    }
+    writeln!(diagram, "    [*] --> {}", machine.initial_state).unwrap();
+    for transition in &transitions {
+        let group = group_of(&transition.from);
+        if group.is_none() || group != group_of(&transition.to) {
+            mermaid_transition(&mut diagram, "    ", transition);
+        }
+    }
+
+    let of_kind = |kind| {
+        machine
+            .states
+            .iter()
+            .filter(move |state| machine.state_info(state).kind == kind)
+    };
+    for state in of_kind(StateKind::Final) {
+        writeln!(diagram, "    {state} --> [*]").unwrap();
+    }
+    for state in &machine.states {
+        if group_of(state).is_none() {
+            mermaid_note(&mut diagram, "    ", machine, state);
+        }
+    }
+    for (kind, class, style) in [
+        (
+            StateKind::Final,
+            "finalState",
+            "fill:#d4edda,stroke:#28a745",
+        ),
+        (
+            StateKind::Error,
+            "errorState",
+            "fill:#f8d7da,stroke:#dc3545",
+        ),
+    ] {
+        let states: Vec<_> = of_kind(kind).map(String::as_str).collect();
+        if !states.is_empty() {
+            writeln!(diagram, "    classDef {class} {style}").unwrap();
+            writeln!(diagram, "    class {} {class}", states.join(",")).unwrap();
+        }
+    }
+    diagram
+}
+
+fn mermaid_transition(diagram: &mut String, indent: &str, transition: &Transition) {
+    let Transition {
+        from,
+        input,
+        to,
+        outputs,
+        ..
+    } = transition;
+    write!(diagram, "{indent}{from} --> {to}: {input}").unwrap();
+    if !outputs.is_empty() {
+        write!(diagram, " [{}]", outputs.join(", ")).unwrap();
+    }
+    diagram.push('\n');
+}
+
+/// Attach the documentation of the state and its outgoing transitions as a
+/// note, if there is any.
+fn mermaid_note(diagram: &mut String, indent: &str, machine: &Machine, state: &str) {
+    let mut lines: Vec<String> = machine
+        .state_info(state)
+        .doc
+        .iter()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect();
+    for transition in &machine.transitions {
+        if transition.from != state || transition.doc.is_empty() {
+            continue;
+        }
+        let doc: Vec<_> = transition.doc.iter().map(|line| line.trim()).collect();
+        lines.push(format!("{}: {}", transition.input, doc.join(" ").trim()));
+    }
+    if lines.is_empty() {
+        return;
+    }
+    writeln!(diagram, "{indent}note right of {state}").unwrap();
+    for line in lines {
+        writeln!(diagram, "{indent}    {line}").unwrap();
+    }
+    writeln!(diagram, "{indent}end note").unwrap();
+}
+
+/// Generate a Graphviz DOT diagram. The initial state is pointed to by an
+/// arrow from a point named `"<start>"`, which is not an identifier and so
+/// cannot clash with a state. States without outgoing transitions are drawn
+/// as double circles. The outputs of the states are added to their labels as
+/// `entry / output`, edges are labeled as `input / output1, output2`.
+pub fn dot(machine: &Machine) -> String {
+    let mut diagram = format!(
+        "digraph {} {{\n    rankdir=LR;\n    \"<start>\" [shape=point];\n",
+        machine.name
+    );
+    let transitions = machine.diagram_transitions();
+    let final_states: Vec<_> = machine.final_states().collect();
+    for state in &machine.states {
+        let shape = if final_states.contains(&state) {
+            "doublecircle"
+        } else {
+            "circle"
+        };
+        match machine.state_info(state).output {
+            Some(output) => writeln!(
+                diagram,
+                "    {state} [shape={shape}, label=\"{state}\\nentry / {output}\"];"
+            ),
+            None => writeln!(diagram, "    {state} [shape={shape}];"),
+        }
+        .unwrap();
+    }
+    writeln!(diagram, "    \"<start>\" -> {};", machine.initial_state).unwrap();
+    for transition in &transitions {
+        let Transition {
+            from,
+            input,
+            to,
+            outputs,
+            ..
+        } = transition;
+        let label = if outputs.is_empty() {
+            input.clone()
+        } else {
+            format!("{input} / {}", outputs.join(", "))
+        };
+        writeln!(diagram, "    {from} -> {to} [label=\"{label}\"];").unwrap();
+    }
+    diagram.push('}');
+    diagram
+}
+
+/// Generate a PlantUML state diagram. States without outgoing transitions are
+/// connected to the final pseudostate. The outputs of the states are
+/// described as `entry / output`, transitions are labeled as
+/// `input / output1, output2`.
+pub fn plantuml(machine: &Machine) -> String {
+    let mut diagram = format!("@startuml\n[*] --> {}\n", machine.initial_state);
+    for state in &machine.states {
+        if let Some(output) = machine.state_info(state).output {
+            writeln!(diagram, "{state} : entry / {output}").unwrap();
+        }
+    }
+    let transitions = machine.diagram_transitions();
+    for transition in &transitions {
+        let Transition {
+            from,
+            input,
+            to,
+            outputs,
+            ..
+        } = transition;
+        write!(diagram, "{from} --> {to} : {input}").unwrap();
+        if !outputs.is_empty() {
+            write!(diagram, " / {}", outputs.join(", ")).unwrap();
+        }
+        diagram.push('\n');
+    }
+    for state in machine.final_states() {
+        writeln!(diagram, "{state} --> [*]").unwrap();
+    }
+    diagram.push_str("@enduml");
+    diagram
+}
+
+/// Generate an SCXML document. Every input is an event and every output is
+/// sent as an event to the parent session (`<send target="#_parent">`), that
+/// is the code running the machine, when the transition is taken, in order.
+/// The outputs of the states are sent in `<onentry>`. Final states without
+/// outgoing transitions are written as `<final>`.
+pub fn scxml(machine: &Machine) -> String {
+    let mut document = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+         <scxml xmlns=\"http://www.w3.org/2005/07/scxml\" version=\"1.0\" \
+         name=\"{}\" initial=\"{}\">\n",
+        machine.name, machine.initial_state
+    );
+    let transitions = machine.diagram_transitions();
+    for state in &machine.states {
+        let outgoing: Vec<_> = transitions
+            .iter()
+            .filter(|transition| transition.from == *state)
+            .collect();
+        let info = machine.state_info(state);
+        let element = match info.kind {
+            StateKind::Final if outgoing.is_empty() => "final",
+            _ => "state",
+        };
+        if outgoing.is_empty() && info.output.is_none() {
+            writeln!(document, "  <{element} id=\"{state}\"/>").unwrap();
+            continue;
+        }
+        writeln!(document, "  <{element} id=\"{state}\">").unwrap();
+        if let Some(output) = &info.output {
+            writeln!(document, "    <onentry>").unwrap();
+            writeln!(
+                document,
+                "      <send event=\"{output}\" target=\"#_parent\"/>"
+            )
+            .unwrap();
+            writeln!(document, "    </onentry>").unwrap();
+        }
+        for transition in outgoing {
+            let Transition {
+                input, to, outputs, ..
+            } = transition;
+            if outputs.is_empty() {
+                writeln!(
+                    document,
+                    "    <transition event=\"{input}\" target=\"{to}\"/>"
+                )
+                .unwrap();
+                continue;
+            }
+            writeln!(
+                document,
+                "    <transition event=\"{input}\" target=\"{to}\">"
+            )
+            .unwrap();
+            for output in outputs {
+                writeln!(
+                    document,
+                    "      <send event=\"{output}\" target=\"#_parent\"/>"
+                )
+                .unwrap();
+            }
+            writeln!(document, "    </transition>").unwrap();
+        }
+        writeln!(document, "  </{element}>").unwrap();
+    }
+    document.push_str("</scxml>");
+    document
+}
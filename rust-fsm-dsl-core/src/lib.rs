@@ -0,0 +1,20 @@
+//! Parser and analysis of the `rust-fsm` DSL shared by the `rust-fsm-dsl`
+//! macros and the `rust-fsm` command line tool. See more in the `rust-fsm`
+//! crate documentation.
+//!
+//! The intermediate representation of machines in the [`model`] module is the
+//! stable part of the API: it can be produced by parsing the DSL
+//! ([`Machine::parse_str`], [`Machine::from_tokens`]) or built directly, and
+//! consumed by the diagram generators, the checks and external tools. The
+//! parser types are an implementation detail of the macros and are not
+//! stable.
+
+pub mod check;
+pub mod codegen;
+pub mod diagram;
+pub mod model;
+pub mod parser;
+pub mod scxml;
+pub mod source;
+
+pub use model::Machine;
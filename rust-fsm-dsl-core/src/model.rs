@@ -0,0 +1,720 @@
+//! The intermediate representation of state machine definitions.
+//!
+//! [`Machine`] describes a machine independently of the way it was defined (the
+//! DSL, an SCXML document or code building it directly). Unlike the parser
+//! types it does not depend on `syn`: all names, paths and attributes are
+//! stored as strings, written the way they are usually written in the source
+//! code (e.g. `pub(crate)` and `crate::Input`). This is the representation the
+//! diagrams, the checks and the code generation are built on.
+//!
+//! The structs are `#[non_exhaustive]`, so that new settings can be added
+//! without breaking the tools built on them: they are created with
+//! [`Machine::new`], [`Transition::new`] and [`Options::default`] and modified
+//! through their fields afterwards.
+
+use crate::parser::{self, StateAttributes, StateMachineDef, TransitionDef, TransitionEntry};
+use proc_macro2::{Delimiter, Spacing, TokenStream, TokenTree};
+use quote::{quote, ToTokens};
+use std::collections::{BTreeMap, BTreeSet};
+use syn::{parse_quote, Attribute, Expr, Lit, Meta};
+
+/// A state machine definition.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct Machine {
+    /// The name of the generated module.
+    pub name: String,
+    /// The visibility modifier of the generated module, e.g. `pub`. Empty for
+    /// private modules.
+    pub visibility: String,
+    /// The generic parameters of the generated types, e.g. `<'a, T: Clone>`.
+    /// Empty for machines without generic parameters.
+    pub generics: String,
+    /// The where clause of the generated types, e.g. `where T: Default`. Empty
+    /// when there is none.
+    pub where_clause: String,
+    /// The lines of the documentation of the machine.
+    pub doc: Vec<String>,
+    /// The attributes applied to the generated types without the `#[` and `]`
+    /// delimiters, e.g. `derive(Debug)`.
+    pub attributes: Vec<String>,
+    /// The path to the custom type used as the input alphabet.
+    pub input_type: Option<String>,
+    /// The path to the custom type used as the set of states.
+    pub state_type: Option<String>,
+    /// The path to the custom type used as the output alphabet.
+    pub output_type: Option<String>,
+    /// The additional items to generate.
+    pub options: Options,
+    pub initial_state: String,
+    /// All states of the machine, sorted by name.
+    pub states: Vec<String>,
+    /// All inputs of the machine, sorted by name.
+    pub inputs: Vec<String>,
+    /// All outputs of the machine, sorted by name.
+    pub outputs: Vec<String>,
+    /// The transitions in the order of their definition. When the same state
+    /// and input are listed more than once only the first transition is used.
+    pub transitions: Vec<Transition>,
+    /// The documentation and the diagram attributes of the states. States
+    /// without any are not listed.
+    pub state_info: BTreeMap<String, StateInfo>,
+}
+
+/// A single state transition.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct Transition {
+    pub from: String,
+    pub input: String,
+    pub to: String,
+    /// The outputs given on the transition, in order.
+    pub outputs: Vec<String>,
+    /// The lines of the documentation of the transition.
+    pub doc: Vec<String>,
+}
+
+/// How the combinations of a state and an input without a transition are
+/// handled, set with `#[state_machine(default = ...)]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefaultPolicy {
+    /// Stay in the same state without an output (`ignore`). These transitions
+    /// are not drawn in the diagrams.
+    Ignore,
+    /// Stay in the same state without an output (`self`). These transitions
+    /// are drawn in the diagrams.
+    SelfLoop,
+    /// Reject the input (`error`).
+    Error,
+}
+
+/// The implementation of the transition and output functions of the generated
+/// machine, set with `#[state_machine(backend = "...")]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    /// A `match` over the combinations of a state and an input (`"match"`).
+    #[default]
+    Match,
+    /// Lookups in dense static tables indexed by the state and the input
+    /// (`"table"`). Suits machines with hundreds of states.
+    Table,
+}
+
+/// The special meaning of a state in the diagrams.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StateKind {
+    #[default]
+    Normal,
+    /// The machine is done when it reaches this state.
+    Final,
+    /// The state represents a failure.
+    Error,
+}
+
+/// The description of a state.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub struct StateInfo {
+    /// The lines of the documentation of the state.
+    pub doc: Vec<String>,
+    pub kind: StateKind,
+    /// The composite state this state belongs to in the diagrams.
+    pub group: Option<String>,
+    /// The output given when the machine enters the state.
+    pub output: Option<String>,
+}
+
+/// The additional items generated for a machine. These are set with the
+/// `#[state_machine(...)]` attribute in the DSL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Options {
+    /// Report groups of equivalent states as compile warnings.
+    pub warn_mergeable: bool,
+    /// Generate the minimized version of the machine.
+    pub minimize: bool,
+    /// Generate the typestate API of the machine.
+    pub typestate: bool,
+    /// The implementation of the transition and output functions.
+    pub backend: Backend,
+    /// Require a transition for every combination of a state and an input,
+    /// except for final states. Satisfied by any default policy.
+    pub complete: bool,
+    /// The handling of the combinations of a state and an input without a
+    /// transition. `None` rejects them, like [`DefaultPolicy::Error`].
+    pub default: Option<DefaultPolicy>,
+    /// Include the Mermaid diagram into the documentation.
+    pub doc_mermaid: bool,
+    /// Include the DOT diagram into the documentation.
+    pub doc_dot: bool,
+    /// Generate the `PLANTUML` constant.
+    pub export_plantuml: bool,
+    /// Generate the `SCXML` constant.
+    pub export_scxml: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            warn_mergeable: false,
+            minimize: false,
+            typestate: false,
+            backend: Backend::Match,
+            complete: false,
+            default: None,
+            doc_mermaid: true,
+            doc_dot: false,
+            export_plantuml: false,
+            export_scxml: false,
+        }
+    }
+}
+
+impl Transition {
+    /// Create a transition without outputs and documentation.
+    pub fn new(from: impl Into<String>, input: impl Into<String>, to: impl Into<String>) -> Self {
+        Self {
+            from: from.into(),
+            input: input.into(),
+            to: to.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the outputs given on the transition, in order.
+    pub fn with_outputs<I>(mut self, outputs: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.outputs = outputs.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Set the lines of the documentation of the transition.
+    pub fn with_doc<I>(mut self, doc: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.doc = doc.into_iter().map(Into::into).collect();
+        self
+    }
+}
+
+impl Machine {
+    /// Create a machine from its transitions. The states, inputs and outputs
+    /// are collected from the transitions.
+    pub fn new(
+        name: impl Into<String>,
+        initial_state: impl Into<String>,
+        transitions: Vec<Transition>,
+    ) -> Self {
+        let mut machine = Self {
+            name: name.into(),
+            initial_state: initial_state.into(),
+            transitions,
+            ..Self::default()
+        };
+        machine.collect_alphabets(Vec::new());
+        machine
+    }
+
+    /// Parse a definition written in the DSL.
+    pub fn parse_str(text: &str) -> syn::Result<Self> {
+        syn::parse_str::<StateMachineDef>(text).map(|def| Self::from(&def))
+    }
+
+    /// Parse a definition written in the DSL from the tokens, e.g. the body of
+    /// a `state_machine!` invocation.
+    pub fn from_tokens(tokens: TokenStream) -> syn::Result<Self> {
+        syn::parse2::<StateMachineDef>(tokens).map(|def| Self::from(&def))
+    }
+
+    /// The index of the state in `states`.
+    pub fn state_index(&self, state: &str) -> Option<usize> {
+        self.states.iter().position(|s| s == state)
+    }
+
+    /// The index of the input in `inputs`.
+    pub fn input_index(&self, input: &str) -> Option<usize> {
+        self.inputs.iter().position(|i| i == input)
+    }
+
+    /// The index of the output in `outputs`.
+    pub fn output_index(&self, output: &str) -> Option<usize> {
+        self.outputs.iter().position(|o| o == output)
+    }
+
+    /// The transitions that take effect, i.e. the first transition for every
+    /// combination of a state and an input.
+    pub fn effective_transitions(&self) -> impl Iterator<Item = &Transition> {
+        self.transitions
+            .iter()
+            .enumerate()
+            .filter(|&(i, transition)| {
+                !self.transitions[..i].iter().any(|previous| {
+                    previous.from == transition.from && previous.input == transition.input
+                })
+            })
+            .map(|(_, transition)| transition)
+    }
+
+    /// The combinations of a state and an input without a transition, in the
+    /// order of `states` and `inputs`.
+    pub fn missing_transitions(&self) -> Vec<(&String, &String)> {
+        self.states
+            .iter()
+            .flat_map(|state| self.inputs.iter().map(move |input| (state, input)))
+            .filter(|&(state, input)| {
+                !self
+                    .transitions
+                    .iter()
+                    .any(|transition| transition.from == *state && transition.input == *input)
+            })
+            .collect()
+    }
+
+    /// The transitions added by the default policy: a self-loop without an
+    /// output for every missing transition with `ignore` and `self`, nothing
+    /// otherwise.
+    pub fn default_transitions(&self) -> Vec<Transition> {
+        match self.options.default {
+            Some(DefaultPolicy::Ignore | DefaultPolicy::SelfLoop) => self
+                .missing_transitions()
+                .into_iter()
+                .map(|(state, input)| Transition {
+                    from: state.clone(),
+                    input: input.clone(),
+                    to: state.clone(),
+                    outputs: Vec::new(),
+                    doc: Vec::new(),
+                })
+                .collect(),
+            Some(DefaultPolicy::Error) | None => Vec::new(),
+        }
+    }
+
+    /// The transitions shown in the diagrams: the defined ones followed by the
+    /// ones added by `default = self`.
+    pub fn diagram_transitions(&self) -> Vec<Transition> {
+        let mut transitions = self.transitions.clone();
+        if self.options.default == Some(DefaultPolicy::SelfLoop) {
+            transitions.extend(self.default_transitions());
+        }
+        transitions
+    }
+
+    /// The outputs given when the transition is taken: the outputs of the
+    /// transition followed by the output of the state it enters.
+    pub fn transition_outputs<'a>(&'a self, transition: &'a Transition) -> Vec<&'a String> {
+        let state_output = self
+            .state_info
+            .get(&transition.to)
+            .and_then(|info| info.output.as_ref());
+        transition.outputs.iter().chain(state_output).collect()
+    }
+
+    /// The description of the state. Empty for states without one.
+    pub fn state_info(&self, state: &str) -> StateInfo {
+        self.state_info.get(state).cloned().unwrap_or_default()
+    }
+
+    /// The groups of states in the order of their names, each listing its
+    /// states in the order of `states`.
+    pub fn groups(&self) -> Vec<(&str, Vec<&String>)> {
+        let mut groups: BTreeMap<&str, Vec<&String>> = BTreeMap::new();
+        for state in &self.states {
+            if let Some(group) = self
+                .state_info
+                .get(state)
+                .and_then(|info| info.group.as_ref())
+            {
+                groups.entry(group).or_default().push(state);
+            }
+        }
+        groups.into_iter().collect()
+    }
+
+    /// The states without outgoing transitions.
+    pub fn final_states(&self) -> impl Iterator<Item = &String> {
+        self.states.iter().filter(|state| {
+            self.transitions
+                .iter()
+                .all(|transition| transition.from != **state)
+        })
+    }
+
+    /// Compute the classes of equivalent states, including the transitions
+    /// added by the default policy. Outputs the class index for every state in
+    /// `states`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a transition refers to a state, an input or an output that is
+    /// not listed in the machine.
+    pub fn equivalence_classes(&self) -> Vec<usize> {
+        let num_inputs = self.inputs.len();
+        let mut transitions_table = vec![None; self.states.len() * num_inputs];
+        let mut outputs_table = vec![Vec::new(); self.states.len() * num_inputs];
+        let default_transitions = self.default_transitions();
+        for transition in self.effective_transitions().chain(&default_transitions) {
+            let index = self.state_index(&transition.from).expect("unknown state") * num_inputs
+                + self.input_index(&transition.input).expect("unknown input");
+            transitions_table[index] =
+                Some(self.state_index(&transition.to).expect("unknown state"));
+            // The transitions added by the default policy do not give outputs.
+            let outputs = if default_transitions.contains(transition) {
+                Vec::new()
+            } else {
+                self.transition_outputs(transition)
+            };
+            outputs_table[index] = outputs
+                .into_iter()
+                .map(|output| self.output_index(output).expect("unknown output"))
+                .collect();
+        }
+        let state_outputs: Vec<_> = self
+            .states
+            .iter()
+            .map(|state| {
+                self.state_info(state)
+                    .output
+                    .map(|output| self.output_index(&output).expect("unknown output"))
+            })
+            .collect();
+
+        let index = |state: usize, input: usize| state * num_inputs + input;
+        rust_fsm_minimize::equivalence_classes(
+            self.states.len(),
+            num_inputs,
+            |state, input| transitions_table[index(state, input)],
+            |state, input| &outputs_table[index(state, input)],
+            |state| state_outputs[state],
+        )
+    }
+
+    /// Fill `states`, `inputs` and `outputs` from the initial state, the given
+    /// additional states and the transitions.
+    fn collect_alphabets(&mut self, extra_states: Vec<String>) {
+        let mut states = BTreeSet::new();
+        let mut inputs = BTreeSet::new();
+        let mut outputs = BTreeSet::new();
+
+        states.insert(self.initial_state.clone());
+        states.extend(extra_states);
+        for transition in &self.transitions {
+            states.insert(transition.from.clone());
+            states.insert(transition.to.clone());
+            inputs.insert(transition.input.clone());
+            outputs.extend(transition.outputs.iter().cloned());
+        }
+        for info in self.state_info.values() {
+            outputs.extend(info.output.clone());
+        }
+
+        self.states = states.into_iter().collect();
+        self.inputs = inputs.into_iter().collect();
+        self.outputs = outputs.into_iter().collect();
+    }
+}
+
+/// Write the tokens the way they are usually written in the source code, e.g.
+/// `Vec<T>` rather than `Vec < T >` as the tokens are displayed.
+fn source_text(tokens: impl ToTokens) -> String {
+    let mut text = String::new();
+    write_tokens(&mut text, tokens.into_token_stream());
+    text
+}
+
+fn write_tokens(text: &mut String, tokens: TokenStream) {
+    let mut previous: Option<TokenTree> = None;
+    // The second colon of `::` is not joint to the following token.
+    let mut path_separator = false;
+    for token in tokens {
+        if let Some(previous) = &previous {
+            if !path_separator && spaced(previous, &token) {
+                text.push(' ');
+            }
+        }
+        path_separator = matches!(
+            (&previous, &token),
+            (Some(TokenTree::Punct(first)), TokenTree::Punct(second))
+                if first.as_char() == ':' && first.spacing() == Spacing::Joint
+                    && second.as_char() == ':'
+        );
+        match &token {
+            TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    Delimiter::Parenthesis => ("(", ")"),
+                    Delimiter::Bracket => ("[", "]"),
+                    Delimiter::Brace => ("{ ", " }"),
+                    Delimiter::None => ("", ""),
+                };
+                text.push_str(open);
+                write_tokens(text, group.stream());
+                text.push_str(close);
+            }
+            token => text.push_str(&token.to_string()),
+        }
+        previous = Some(token);
+    }
+}
+
+/// Whether the tokens are separated by a space.
+fn spaced(previous: &TokenTree, next: &TokenTree) -> bool {
+    match (previous, next) {
+        // `::`, `->` and lifetimes.
+        (TokenTree::Punct(punct), _) if punct.spacing() == Spacing::Joint => false,
+        (TokenTree::Punct(punct), _) if matches!(punct.as_char(), '<' | '&' | '!' | '.' | '?') => {
+            false
+        }
+        (_, TokenTree::Punct(punct)) => {
+            !matches!(punct.as_char(), ',' | ';' | ':' | '<' | '>' | '!' | '.')
+        }
+        // `derive(Debug)`, `pub(crate)`, `Fn(u8)`.
+        (TokenTree::Ident(_), TokenTree::Group(group)) => {
+            group.delimiter() != Delimiter::Parenthesis
+        }
+        _ => true,
+    }
+}
+
+/// Extract the lines of the documentation from `#[doc = "..."]` attributes.
+fn doc_lines(attributes: &[Attribute]) -> Vec<String> {
+    attributes
+        .iter()
+        .filter_map(|attribute| match &attribute.meta {
+            Meta::NameValue(meta) => match &meta.value {
+                Expr::Lit(expr) => match &expr.lit {
+                    Lit::Str(line) => Some(line.value()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+/// Build `#[doc = "..."]` attributes from the lines of the documentation.
+fn doc_attributes(lines: &[String]) -> Vec<Attribute> {
+    lines
+        .iter()
+        .map(|line| parse_quote!(#[doc = #line]))
+        .collect()
+}
+
+impl From<&StateMachineDef> for Machine {
+    fn from(def: &StateMachineDef) -> Self {
+        let transitions = def
+            .transitions
+            .iter()
+            .flat_map(|def| {
+                def.transitions.iter().map(move |entry| Transition {
+                    from: def.initial_state.to_string(),
+                    input: entry.input_value.to_string(),
+                    to: entry.final_state.to_string(),
+                    outputs: entry.outputs.iter().map(ToString::to_string).collect(),
+                    doc: doc_lines(&entry.doc),
+                })
+            })
+            .collect();
+
+        let mut state_info = BTreeMap::<String, StateInfo>::new();
+        for def in &def.transitions {
+            let StateAttributes { kind, group } = &def.state_attributes;
+            if def.doc.is_empty() && kind.is_none() && group.is_none() && def.state_output.is_none()
+            {
+                continue;
+            }
+            let info = state_info.entry(def.initial_state.to_string()).or_default();
+            info.doc.extend(doc_lines(&def.doc));
+            match kind {
+                Some(parser::StateKind::Final) => info.kind = StateKind::Final,
+                Some(parser::StateKind::Error) => info.kind = StateKind::Error,
+                None => {}
+            }
+            if let Some(group) = group {
+                info.group = Some(group.to_string());
+            }
+            if let Some(output) = &def.state_output {
+                info.output = Some(output.to_string());
+            }
+        }
+
+        let path_string = |path: &Option<syn::Path>| path.as_ref().map(source_text);
+
+        let mut machine = Self {
+            name: def.name.to_string(),
+            visibility: source_text(&def.visibility),
+            generics: source_text(&def.generics),
+            where_clause: source_text(&def.generics.where_clause),
+            doc: doc_lines(&def.doc),
+            attributes: def
+                .attributes
+                .iter()
+                .map(|attribute| source_text(&attribute.meta))
+                .collect(),
+            input_type: path_string(&def.input_type),
+            state_type: path_string(&def.state_type),
+            output_type: path_string(&def.output_type),
+            options: Options {
+                warn_mergeable: def.warn_mergeable,
+                minimize: def.minimize,
+                typestate: def.typestate,
+                backend: match def.backend {
+                    parser::Backend::Match => Backend::Match,
+                    parser::Backend::Table => Backend::Table,
+                },
+                complete: def.complete,
+                default: def.default_policy.map(|policy| match policy {
+                    parser::DefaultPolicy::Ignore => DefaultPolicy::Ignore,
+                    parser::DefaultPolicy::SelfLoop => DefaultPolicy::SelfLoop,
+                    parser::DefaultPolicy::Error => DefaultPolicy::Error,
+                }),
+                doc_mermaid: def.doc_diagrams.mermaid,
+                doc_dot: def.doc_diagrams.dot,
+                export_plantuml: def.exports.plantuml,
+                export_scxml: def.exports.scxml,
+            },
+            initial_state: def.initial_state.to_string(),
+            transitions,
+            state_info,
+            ..Self::default()
+        };
+        // States without outgoing transitions may still be declared on their own.
+        let declared = def
+            .transitions
+            .iter()
+            .map(|def| def.initial_state.to_string())
+            .collect();
+        machine.collect_alphabets(declared);
+        machine
+    }
+}
+
+impl TryFrom<&Machine> for StateMachineDef {
+    type Error = syn::Error;
+
+    /// Convert the machine back into the definition, as if it was written in
+    /// the DSL. Fails if any of the names, paths or attributes is invalid.
+    fn try_from(machine: &Machine) -> syn::Result<Self> {
+        let mut attributes = doc_attributes(&machine.doc);
+        for attribute in &machine.attributes {
+            let meta: Meta = syn::parse_str(attribute)?;
+            attributes.push(parse_quote!(#[#meta]));
+        }
+
+        let options = &machine.options;
+        let mut settings = Vec::new();
+        if options.warn_mergeable {
+            settings.push(quote!(warn_mergeable));
+        }
+        if options.minimize {
+            settings.push(quote!(minimize));
+        }
+        if options.typestate {
+            settings.push(quote!(typestate));
+        }
+        if options.backend == Backend::Table {
+            settings.push(quote!(backend = "table"));
+        }
+        if options.complete {
+            settings.push(quote!(complete));
+        }
+        match options.default {
+            Some(DefaultPolicy::Ignore) => settings.push(quote!(default = ignore)),
+            Some(DefaultPolicy::SelfLoop) => settings.push(quote!(default = self)),
+            Some(DefaultPolicy::Error) => settings.push(quote!(default = error)),
+            None => {}
+        }
+        if !options.doc_mermaid || options.doc_dot {
+            let formats = [
+                options.doc_mermaid.then(|| quote!(mermaid)),
+                options.doc_dot.then(|| quote!(dot)),
+            ];
+            let formats = formats.iter().flatten();
+            settings.push(quote!(diagram(#(#formats),*)));
+        }
+        if options.export_plantuml || options.export_scxml {
+            let formats = [
+                options.export_plantuml.then(|| quote!(plantuml)),
+                options.export_scxml.then(|| quote!(scxml)),
+            ];
+            let formats = formats.iter().flatten();
+            settings.push(quote!(export(#(#formats),*)));
+        }
+        for (key, path) in [
+            (quote!(input), &machine.input_type),
+            (quote!(state), &machine.state_type),
+            (quote!(output), &machine.output_type),
+        ] {
+            if let Some(path) = path {
+                let path: syn::Path = syn::parse_str(path)?;
+                settings.push(quote!(#key(#path)));
+            }
+        }
+        if !settings.is_empty() {
+            attributes.push(parse_quote!(#[state_machine(#(#settings),*)]));
+        }
+
+        let mut transitions = Vec::new();
+        for transition in &machine.transitions {
+            transitions.push(TransitionDef {
+                doc: Vec::new(),
+                state_attributes: StateAttributes::default(),
+                initial_state: syn::parse_str(&transition.from)?,
+                state_output: None,
+                transitions: vec![TransitionEntry {
+                    doc: doc_attributes(&transition.doc),
+                    input_value: syn::parse_str(&transition.input)?,
+                    final_state: syn::parse_str(&transition.to)?,
+                    outputs: transition
+                        .outputs
+                        .iter()
+                        .map(|output| syn::parse_str(output))
+                        .collect::<syn::Result<_>>()?,
+                }],
+            });
+        }
+        // Declare the states with descriptions and the states not mentioned in
+        // any transition.
+        for state in &machine.states {
+            let info = machine.state_info(state);
+            let mentioned = *state == machine.initial_state
+                || machine
+                    .transitions
+                    .iter()
+                    .any(|transition| transition.from == *state || transition.to == *state);
+            if mentioned && info == StateInfo::default() {
+                continue;
+            }
+            transitions.push(TransitionDef {
+                doc: doc_attributes(&info.doc),
+                state_attributes: StateAttributes {
+                    kind: match info.kind {
+                        StateKind::Normal => None,
+                        StateKind::Final => Some(parser::StateKind::Final),
+                        StateKind::Error => Some(parser::StateKind::Error),
+                    },
+                    group: info.group.as_deref().map(syn::parse_str).transpose()?,
+                },
+                initial_state: syn::parse_str(state)?,
+                state_output: info.output.as_deref().map(syn::parse_str).transpose()?,
+                transitions: Vec::new(),
+            });
+        }
+
+        let mut def = StateMachineDef::new(
+            attributes,
+            syn::parse_str(&machine.visibility)?,
+            syn::parse_str(&machine.name)?,
+            syn::parse_str(&machine.initial_state)?,
+            transitions,
+        )?;
+        def.generics = syn::parse_str(&machine.generics)?;
+        def.generics.where_clause = syn::parse_str(&machine.where_clause)?;
+        Ok(def)
+    }
+}
@@ -0,0 +1,537 @@
+use syn::{
+    braced, bracketed,
+    ext::IdentExt,
+    parenthesized,
+    parse::{Error, Parse, ParseStream, Result},
+    token::{Bracket, Paren},
+    Attribute, Generics, Ident, LitStr, Path, Token, Visibility,
+};
+
+pub(crate) mod kw {
+    syn::custom_keyword!(include);
+}
+
+/// The outputs of a state transition: `[Output]` or `[Output1, Output2]`
+pub struct Output(Vec<Ident>);
+
+impl Parse for Output {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.lookahead1().peek(Bracket) {
+            let output_content;
+            let brackets = bracketed!(output_content in input);
+            let outputs: Vec<_> = output_content
+                .parse_terminated(Ident::parse, Token![,])?
+                .into_iter()
+                .collect();
+            if outputs.is_empty() {
+                return Err(Error::new(brackets.span.join(), "expected an output"));
+            }
+            Ok(Self(outputs))
+        } else {
+            Ok(Self(Vec::new()))
+        }
+    }
+}
+
+impl From<Output> for Vec<Ident> {
+    fn from(output: Output) -> Self {
+        output.0
+    }
+}
+
+/// Represents a part of state transition without the initial state. The `Parse`
+/// trait is implemented for the compact form.
+pub struct TransitionEntry {
+    /// The doc comments of the transition
+    pub doc: Vec<Attribute>,
+    pub input_value: Ident,
+    pub final_state: Ident,
+    /// The outputs given on the transition, in order
+    pub outputs: Vec<Ident>,
+}
+
+impl Parse for TransitionEntry {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let doc = Attribute::parse_outer(input)?;
+        if let Some(attribute) = doc.iter().find(|a| !a.path().is_ident("doc")) {
+            return Err(Error::new_spanned(
+                attribute,
+                "only doc comments are allowed on transitions",
+            ));
+        }
+        let input_value = input.parse()?;
+        input.parse::<Token![=>]>()?;
+        let final_state = input.parse()?;
+        let outputs = input.parse::<Output>()?.into();
+        Ok(Self {
+            doc,
+            input_value,
+            final_state,
+            outputs,
+        })
+    }
+}
+
+/// The special meaning of a state in the diagrams
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StateKind {
+    Final,
+    Error,
+}
+
+/// The properties of a state set with the `#[state(...)]` attribute
+#[derive(Default)]
+pub struct StateAttributes {
+    pub kind: Option<StateKind>,
+    /// The composite state this state is grouped into
+    pub group: Option<Ident>,
+}
+
+impl StateAttributes {
+    fn parse_attribute(&mut self, attribute: &Attribute) -> Result<()> {
+        attribute.parse_nested_meta(|meta| {
+            let kind = if meta.path.is_ident("final") {
+                StateKind::Final
+            } else if meta.path.is_ident("error") {
+                StateKind::Error
+            } else if meta.path.is_ident("group") {
+                let content;
+                parenthesized!(content in meta.input);
+                self.group = Some(content.parse()?);
+                return Ok(());
+            } else {
+                return Err(meta.error("unsupported state attribute"));
+            };
+            if self.kind.is_some_and(|previous| previous != kind) {
+                return Err(meta.error("a state cannot be both final and error"));
+            }
+            self.kind = Some(kind);
+            Ok(())
+        })
+    }
+}
+
+/// Parses the transition in any of the possible formats. A state may also be
+/// declared without any transitions to document it or set its attributes.
+pub struct TransitionDef {
+    /// The doc comments of the state. In the simple format the doc comments
+    /// describe the transition instead.
+    pub doc: Vec<Attribute>,
+    pub state_attributes: StateAttributes,
+    pub initial_state: Ident,
+    /// The output given when the machine enters the state.
+    pub state_output: Option<Ident>,
+    pub transitions: Vec<TransitionEntry>,
+}
+
+impl Parse for TransitionDef {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut doc = Vec::new();
+        let mut state_attributes = StateAttributes::default();
+        for attribute in Attribute::parse_outer(input)? {
+            if attribute.path().is_ident("state") {
+                state_attributes.parse_attribute(&attribute)?;
+            } else if attribute.path().is_ident("doc") {
+                doc.push(attribute);
+            } else {
+                return Err(Error::new_spanned(
+                    attribute,
+                    "only doc comments and `#[state(...)]` are allowed on states",
+                ));
+            }
+        }
+
+        let initial_state = input.parse()?;
+        // The output of the state itself: InitialState [Output]
+        let state_output = if input.peek(Bracket) {
+            let output_content;
+            bracketed!(output_content in input);
+            let output = output_content.parse()?;
+            if !output_content.is_empty() {
+                return Err(output_content.error("a state can have only one output"));
+            }
+            Some(output)
+        } else {
+            None
+        };
+        // Parse the transition in the simple format
+        // InitialState(Input) => ResultState [Output]
+        let transitions = if input.lookahead1().peek(Paren) {
+            let input_content;
+            parenthesized!(input_content in input);
+            let input_value = input_content.parse()?;
+            input.parse::<Token![=>]>()?;
+            let final_state = input.parse()?;
+            let outputs = input.parse::<Output>()?.into();
+
+            vec![TransitionEntry {
+                doc: std::mem::take(&mut doc),
+                input_value,
+                final_state,
+                outputs,
+            }]
+        } else if input.is_empty() || input.peek(Token![,]) {
+            // A declaration of the state without transitions
+            Vec::new()
+        } else {
+            // Parse the transition in the compact format
+            // InitialState => {
+            //     Input1 => State1,
+            //     Input2 => State2 [Output]
+            // }
+            input.parse::<Token![=>]>()?;
+            let entries_content;
+            braced!(entries_content in input);
+
+            let entries: Vec<_> = entries_content
+                .parse_terminated(TransitionEntry::parse, Token![,])?
+                .into_iter()
+                .collect();
+            if entries.is_empty() {
+                return Err(Error::new_spanned(
+                    initial_state,
+                    "No transitions provided for a compact representation",
+                ));
+            }
+            entries
+        };
+        Ok(Self {
+            doc,
+            state_attributes,
+            initial_state,
+            state_output,
+            transitions,
+        })
+    }
+}
+
+/// The diagram formats to include into the documentation
+pub struct DocDiagrams {
+    pub mermaid: bool,
+    pub dot: bool,
+}
+
+impl Default for DocDiagrams {
+    fn default() -> Self {
+        Self {
+            mermaid: true,
+            dot: false,
+        }
+    }
+}
+
+/// How the combinations of a state and an input without a transition are
+/// handled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefaultPolicy {
+    /// Stay in the same state without drawing the transition in the diagrams
+    Ignore,
+    /// Stay in the same state, the transition is drawn in the diagrams
+    SelfLoop,
+    /// Reject the input
+    Error,
+}
+
+/// The implementation of the transition and output functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Backend {
+    /// A `match` over the combinations of a state and an input
+    #[default]
+    Match,
+    /// Lookups in static tables indexed by the state and the input
+    Table,
+}
+
+/// The additional documents describing the machine to generate as constants
+#[derive(Default)]
+pub struct Exports {
+    pub plantuml: bool,
+    pub scxml: bool,
+}
+
+/// Parses the whole state machine definition in the following form (example):
+///
+/// ```rust,ignore
+/// state_machine! {
+///     CircuitBreaker(Closed)
+///
+///     Closed(Unsuccessful) => Open [SetupTimer],
+///     Open(TimerTriggered) => HalfOpen,
+///     HalfOpen => {
+///         Successful => Closed,
+///         Unsuccessful => Open [SetupTimer]
+///     }
+/// }
+/// ```
+///
+/// The same definition can be loaded from a file:
+///
+/// ```rust,ignore
+/// state_machine!(include = "circuit_breaker.fsm");
+/// ```
+pub struct StateMachineDef {
+    pub doc: Vec<Attribute>,
+    /// The visibility modifier (applies to all generated items)
+    pub visibility: Visibility,
+    pub name: Ident,
+    /// The generic parameters and the where clause of the machine, applied to
+    /// all generated types
+    pub generics: Generics,
+    pub initial_state: Ident,
+    pub transitions: Vec<TransitionDef>,
+    pub attributes: Vec<Attribute>,
+    pub input_type: Option<Path>,
+    pub state_type: Option<Path>,
+    pub output_type: Option<Path>,
+    /// Report groups of equivalent states as compile warnings
+    pub warn_mergeable: bool,
+    /// Generate the minimized version of the machine
+    pub minimize: bool,
+    /// Generate the typestate API of the machine
+    pub typestate: bool,
+    /// The implementation of the transition and output functions
+    pub backend: Backend,
+    /// Require a transition for every combination of a state and an input
+    pub complete: bool,
+    /// The handling of the combinations without transitions
+    pub default_policy: Option<DefaultPolicy>,
+    /// The diagrams to include into the documentation (only with the `diagram`
+    /// feature)
+    pub doc_diagrams: DocDiagrams,
+    pub exports: Exports,
+    /// The files the definition was loaded from, the including files first
+    pub source_files: Vec<String>,
+}
+
+impl StateMachineDef {
+    /// Build the definition from its parts, processing the attributes of the
+    /// state machine.
+    pub fn new(
+        attributes: Vec<Attribute>,
+        visibility: Visibility,
+        name: Ident,
+        initial_state: Ident,
+        transitions: Vec<TransitionDef>,
+    ) -> Result<Self> {
+        let mut state_machine_attributes = Vec::new();
+        let mut doc = Vec::new();
+        let attributes = attributes
+            .into_iter()
+            .filter_map(|attribute| {
+                if attribute.path().is_ident("state_machine") {
+                    state_machine_attributes.push(attribute);
+                    None
+                } else if attribute.path().is_ident("doc") {
+                    doc.push(attribute);
+                    None
+                } else {
+                    Some(attribute)
+                }
+            })
+            .collect();
+
+        let mut input_type = None;
+        let mut state_type = None;
+        let mut output_type = None;
+        let mut warn_mergeable = false;
+        let mut minimize = false;
+        let mut typestate = false;
+        let mut backend = Backend::default();
+        let mut complete = false;
+        let mut default_policy = None;
+        let mut doc_diagrams = DocDiagrams::default();
+        let mut exports = Exports::default();
+
+        for attribute in state_machine_attributes {
+            attribute.parse_nested_meta(|meta| {
+                if meta.path.is_ident("warn_mergeable") {
+                    warn_mergeable = true;
+                    return Ok(());
+                } else if meta.path.is_ident("minimize") {
+                    minimize = true;
+                    return Ok(());
+                } else if meta.path.is_ident("typestate") {
+                    typestate = true;
+                    return Ok(());
+                } else if meta.path.is_ident("backend") {
+                    let value: LitStr = meta.value()?.parse()?;
+                    backend = match value.value().as_str() {
+                        "match" => Backend::Match,
+                        "table" => Backend::Table,
+                        _ => {
+                            return Err(Error::new_spanned(
+                                value,
+                                "expected `\"match\"` or `\"table\"`",
+                            ))
+                        }
+                    };
+                    return Ok(());
+                } else if meta.path.is_ident("complete") {
+                    complete = true;
+                    return Ok(());
+                } else if meta.path.is_ident("default") {
+                    let policy = meta.value()?.call(Ident::parse_any)?;
+                    default_policy = Some(match policy.to_string().as_str() {
+                        "ignore" => DefaultPolicy::Ignore,
+                        "self" => DefaultPolicy::SelfLoop,
+                        "error" => DefaultPolicy::Error,
+                        _ => {
+                            return Err(Error::new_spanned(
+                                policy,
+                                "expected `ignore`, `self` or `error`",
+                            ))
+                        }
+                    });
+                    return Ok(());
+                } else if meta.path.is_ident("diagram") {
+                    doc_diagrams = DocDiagrams {
+                        mermaid: false,
+                        dot: false,
+                    };
+                    return meta.parse_nested_meta(|format| {
+                        if format.path.is_ident("mermaid") {
+                            doc_diagrams.mermaid = true;
+                        } else if format.path.is_ident("dot") {
+                            doc_diagrams.dot = true;
+                        } else {
+                            return Err(format.error("unsupported diagram format"));
+                        }
+                        Ok(())
+                    });
+                } else if meta.path.is_ident("export") {
+                    return meta.parse_nested_meta(|format| {
+                        if format.path.is_ident("plantuml") {
+                            exports.plantuml = true;
+                        } else if format.path.is_ident("scxml") {
+                            exports.scxml = true;
+                        } else {
+                            return Err(format.error("unsupported export format"));
+                        }
+                        Ok(())
+                    });
+                }
+
+                let content;
+                parenthesized!(content in meta.input);
+                let p: Path = content.parse()?;
+
+                if meta.path.is_ident("input") {
+                    input_type = Some(p);
+                } else if meta.path.is_ident("state") {
+                    state_type = Some(p);
+                } else if meta.path.is_ident("output") {
+                    output_type = Some(p);
+                }
+
+                Ok(())
+            })?;
+        }
+
+        check_state_attributes(&transitions)?;
+
+        Ok(Self {
+            doc,
+            visibility,
+            name,
+            generics: Generics::default(),
+            initial_state,
+            transitions,
+            attributes,
+            input_type,
+            state_type,
+            output_type,
+            warn_mergeable,
+            minimize,
+            typestate,
+            backend,
+            complete,
+            default_policy,
+            doc_diagrams,
+            exports,
+            source_files: Vec::new(),
+        })
+    }
+}
+
+/// Make sure the attributes and the outputs of the same state set in different
+/// places do not contradict each other and the groups do not clash with the
+/// states.
+fn check_state_attributes(transitions: &[TransitionDef]) -> Result<()> {
+    for (i, def) in transitions.iter().enumerate() {
+        let attributes = &def.state_attributes;
+        if let Some(group) = &attributes.group {
+            let clashes = transitions.iter().any(|def| {
+                def.initial_state == *group
+                    || def
+                        .transitions
+                        .iter()
+                        .any(|entry| entry.final_state == *group)
+            });
+            if clashes {
+                return Err(Error::new_spanned(
+                    group,
+                    "the name of a group cannot be the name of a state",
+                ));
+            }
+        }
+        for previous in &transitions[..i] {
+            if previous.initial_state != def.initial_state {
+                continue;
+            }
+            if let (Some(output), Some(previous)) = (&def.state_output, &previous.state_output) {
+                if output != previous {
+                    return Err(Error::new_spanned(
+                        output,
+                        format!("the state already has the `{previous}` output"),
+                    ));
+                }
+            }
+            let previous = &previous.state_attributes;
+            if let (Some(kind), Some(previous)) = (attributes.kind, previous.kind) {
+                if kind != previous {
+                    return Err(Error::new_spanned(
+                        &def.initial_state,
+                        "a state cannot be both final and error",
+                    ));
+                }
+            }
+            if let (Some(group), Some(previous)) = (&attributes.group, &previous.group) {
+                if group != previous {
+                    return Err(Error::new_spanned(
+                        group,
+                        format!("the state is already grouped into `{previous}`"),
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+impl Parse for StateMachineDef {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.peek(kw::include) && input.peek2(Token![=]) {
+            let crate::source::Include(path) = input.parse()?;
+            return crate::source::load_dsl(&path);
+        }
+
+        let attributes = Attribute::parse_outer(input)?;
+        let visibility = input.parse()?;
+        let name = input.parse()?;
+        let mut generics: Generics = input.parse()?;
+
+        let initial_state_content;
+        parenthesized!(initial_state_content in input);
+        let initial_state = initial_state_content.parse()?;
+        generics.where_clause = input.parse()?;
+
+        let transitions = input
+            .parse_terminated(TransitionDef::parse, Token![,])?
+            .into_iter()
+            .collect();
+
+        let mut def = Self::new(attributes, visibility, name, initial_state, transitions)?;
+        def.generics = generics;
+        Ok(def)
+    }
+}
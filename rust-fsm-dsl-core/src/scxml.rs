@@ -0,0 +1,244 @@
+//! Loading state machine definitions from SCXML documents.
+//!
+//! Only flat machines are supported: top-level `<state>` and `<final>`
+//! elements with `<transition>` elements having a single event and a single
+//! target. Outputs are taken from the `<send>` and `<raise>` elements inside a
+//! transition, in order, and the output of a state from the single `<send>` or
+//! `<raise>` element inside its `<onentry>` element. A `<send>` element may
+//! only target the parent session (`#_parent`), as the documents generated by
+//! `#[state_machine(export(scxml))]` do.
+
+use crate::parser::{StateAttributes, StateKind, StateMachineDef, TransitionDef, TransitionEntry};
+use proc_macro2::Span;
+use roxmltree::{Document, Node};
+use syn::{
+    parse::{Error, Parse, ParseStream, Result},
+    Attribute, Ident, LitStr, Token, Visibility,
+};
+
+/// Parses the input of the `state_machine_from_file` macro:
+///
+/// ```rust,ignore
+/// state_machine_from_file! {
+///     #[derive(Debug)]
+///     pub circuit_breaker = "circuit_breaker.scxml"
+/// }
+/// ```
+///
+/// The name may be omitted, in which case the `name` attribute of the document
+/// is used.
+pub struct ScxmlDef {
+    attributes: Vec<Attribute>,
+    visibility: Visibility,
+    name: Option<Ident>,
+    path: LitStr,
+}
+
+impl Parse for ScxmlDef {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let attributes = Attribute::parse_outer(input)?;
+        let visibility = input.parse()?;
+        let name = if input.peek(Ident) {
+            let name = input.parse()?;
+            input.parse::<Token![=]>()?;
+            Some(name)
+        } else {
+            None
+        };
+        let path = input.parse()?;
+        Ok(Self {
+            attributes,
+            visibility,
+            name,
+            path,
+        })
+    }
+}
+
+impl ScxmlDef {
+    /// Read the document and convert it into a state machine definition.
+    pub fn load(self) -> Result<StateMachineDef> {
+        let span = self.path.span();
+        let (path, text) = crate::source::read(&self.path)?;
+
+        let document =
+            Document::parse(&text).map_err(|error| Error::new(span, format!("{path}: {error}")))?;
+        let loader = Loader {
+            path: &path,
+            span,
+            document: &document,
+        };
+        let (name, initial_state, transitions) = loader.load()?;
+        let name = match self.name {
+            Some(name) => name,
+            None => name.ok_or_else(|| {
+                Error::new(
+                    span,
+                    format!(
+                        "{path}: the document has no name, specify the machine name explicitly"
+                    ),
+                )
+            })?,
+        };
+
+        let mut def = StateMachineDef::new(
+            self.attributes,
+            self.visibility,
+            name,
+            initial_state,
+            transitions,
+        )?;
+        def.source_files.push(path);
+        Ok(def)
+    }
+}
+
+struct Loader<'a, 'input> {
+    path: &'a str,
+    span: Span,
+    document: &'a Document<'input>,
+}
+
+impl Loader<'_, '_> {
+    fn load(&self) -> Result<(Option<Ident>, Ident, Vec<TransitionDef>)> {
+        let root = self.document.root_element();
+        if root.tag_name().name() != "scxml" {
+            return Err(self.error(root, "the root element must be <scxml>"));
+        }
+        let name = root
+            .attribute("name")
+            .map(|name| self.ident(root, name))
+            .transpose()?;
+
+        let mut transitions = Vec::new();
+        for node in root.children().filter(Node::is_element) {
+            match node.tag_name().name() {
+                "state" | "final" => transitions.push(self.state(node)?),
+                // Data models and scripts do not affect the structure of the
+                // machine.
+                "datamodel" | "script" => {}
+                other => {
+                    return Err(self.error(node, &format!("unsupported element <{other}>")));
+                }
+            }
+        }
+
+        let initial_state = match root.attribute("initial") {
+            Some(initial) => self.ident(root, initial)?,
+            None => match transitions.first() {
+                Some(def) => def.initial_state.clone(),
+                None => return Err(self.error(root, "the document has no states")),
+            },
+        };
+
+        Ok((name, initial_state, transitions))
+    }
+
+    fn state(&self, node: Node) -> Result<TransitionDef> {
+        let id = node
+            .attribute("id")
+            .ok_or_else(|| self.error(node, "a state must have an id"))?;
+        let initial_state = self.ident(node, id)?;
+
+        let mut transitions = Vec::new();
+        let mut state_output = None;
+        for child in node.children().filter(Node::is_element) {
+            match child.tag_name().name() {
+                "transition" => transitions.push(self.transition(child)?),
+                "onentry" => {
+                    let mut outputs = child.children().filter(Node::is_element);
+                    let output = match (outputs.next(), outputs.next()) {
+                        (Some(output), None) if state_output.is_none() => output,
+                        _ => return Err(self.error(child, "a state can have only one output")),
+                    };
+                    state_output = Some(self.output(output)?);
+                }
+                "state" | "parallel" | "final" | "initial" | "history" => {
+                    return Err(self.error(child, "nested states are not supported"));
+                }
+                other => {
+                    return Err(self.error(child, &format!("unsupported element <{other}>")));
+                }
+            }
+        }
+
+        let state_attributes = StateAttributes {
+            kind: (node.tag_name().name() == "final").then_some(StateKind::Final),
+            group: None,
+        };
+        Ok(TransitionDef {
+            doc: Vec::new(),
+            state_attributes,
+            initial_state,
+            state_output,
+            transitions,
+        })
+    }
+
+    fn transition(&self, node: Node) -> Result<TransitionEntry> {
+        if node.has_attribute("cond") {
+            return Err(self.error(node, "conditional transitions are not supported"));
+        }
+        let event = node
+            .attribute("event")
+            .ok_or_else(|| self.error(node, "a transition must have an event"))?;
+        if event.split_whitespace().count() != 1 {
+            return Err(self.error(node, "a transition must have exactly one event"));
+        }
+        let target = node
+            .attribute("target")
+            .ok_or_else(|| self.error(node, "a transition must have a target"))?;
+        if target.split_whitespace().count() != 1 {
+            return Err(self.error(node, "a transition must have exactly one target"));
+        }
+
+        let mut outputs = Vec::new();
+        for child in node.children().filter(Node::is_element) {
+            outputs.push(self.output(child)?);
+        }
+
+        Ok(TransitionEntry {
+            doc: Vec::new(),
+            input_value: self.ident(node, event.trim())?,
+            final_state: self.ident(node, target.trim())?,
+            outputs,
+        })
+    }
+
+    /// Reads an output from a `<send>` or `<raise>` element. Events sent
+    /// elsewhere than to the parent session are not outputs of the machine.
+    fn output(&self, node: Node) -> Result<Ident> {
+        match node.tag_name().name() {
+            "send" | "raise" => {
+                if let Some(target) = node.attribute("target") {
+                    if target != "#_parent" {
+                        return Err(self.error(
+                            node,
+                            &format!("an output must be sent to `#_parent`, not `{target}`"),
+                        ));
+                    }
+                }
+                let event = node
+                    .attribute("event")
+                    .ok_or_else(|| self.error(node, "an output must have an event"))?;
+                self.ident(node, event)
+            }
+            other => Err(self.error(node, &format!("unsupported element <{other}>"))),
+        }
+    }
+
+    fn ident(&self, node: Node, value: &str) -> Result<Ident> {
+        let mut ident: Ident = syn::parse_str(value)
+            .map_err(|_| self.error(node, &format!("`{value}` is not a valid Rust identifier")))?;
+        ident.set_span(self.span);
+        Ok(ident)
+    }
+
+    fn error(&self, node: Node, message: &str) -> Error {
+        let position = self.document.text_pos_at(node.range().start);
+        Error::new(
+            self.span,
+            format!("{}:{}:{}: {message}", self.path, position.row, position.col),
+        )
+    }
+}
@@ -0,0 +1,233 @@
+//! Loading state machine definitions from external files.
+
+use crate::parser::{kw, StateMachineDef, TransitionDef};
+use std::{
+    cell::RefCell,
+    path::{Path, PathBuf},
+};
+use syn::{
+    parse::{Error, Parse, ParseStream, Result},
+    LitStr, Token,
+};
+
+thread_local! {
+    /// The files being loaded, the innermost last. Used to resolve the nested
+    /// includes and to detect include cycles.
+    static INCLUDES: RefCell<Vec<PathBuf>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Read the file at the given path. Outputs the full path of the file and its
+/// contents.
+///
+/// The path is relative to the directory of the file being loaded when the
+/// file is included from another file, and to the directory containing the
+/// manifest of the crate being compiled otherwise.
+pub fn read(path: &LitStr) -> Result<(String, String)> {
+    read_in(&base_dir(path)?, path)
+}
+
+/// Read the file at the given path relative to the given directory. Outputs
+/// the full path of the file and its contents.
+pub fn read_in(base_dir: &Path, path: &LitStr) -> Result<(String, String)> {
+    let full_path = base_dir.join(path.value());
+    let full_path = full_path.to_string_lossy().into_owned();
+    let text = std::fs::read_to_string(&full_path)
+        .map_err(|error| Error::new(path.span(), format!("cannot read {full_path}: {error}")))?;
+    Ok((full_path, text))
+}
+
+/// The directory the paths are relative to, see [`read`].
+fn base_dir(path: &LitStr) -> Result<PathBuf> {
+    match INCLUDES.with(|includes| includes.borrow().last().cloned()) {
+        Some(including) => Ok(including.parent().map(Path::to_owned).unwrap_or_default()),
+        None => std::env::var("CARGO_MANIFEST_DIR")
+            .map(PathBuf::from)
+            .map_err(|_| Error::new(path.span(), "CARGO_MANIFEST_DIR is not set")),
+    }
+}
+
+/// Load the state machine definition written in the DSL from the given file.
+/// The path is resolved as described in [`read`]. See [`load_dsl_in`].
+pub fn load_dsl(path: &LitStr) -> Result<StateMachineDef> {
+    load_dsl_in(&base_dir(path)?, path)
+}
+
+/// Load the state machine definition written in the DSL from the file at the
+/// given path relative to the given directory. A file consisting of
+/// `include = "path"` loads the definition from another file relative to it.
+/// Every file read is added to the source files of the definition.
+///
+/// The tokens parsed from a string do not carry their positions, so when the
+/// definition is invalid the header and every transition are parsed on their
+/// own to find the first invalid one, and the error is reported on the path
+/// with the position of that part in the file. The errors in the included
+/// files are reported with the positions in those files.
+pub fn load_dsl_in(base_dir: &Path, path: &LitStr) -> Result<StateMachineDef> {
+    load_dsl_tracked_in(base_dir, path, &mut Vec::new())
+}
+
+/// Load the state machine definition like [`load_dsl_in`] and add the full
+/// path of every file read to `files`, also when the definition is invalid,
+/// so that the files can be watched for the fixes.
+pub fn load_dsl_tracked_in(
+    base_dir: &Path,
+    path: &LitStr,
+    files: &mut Vec<String>,
+) -> Result<StateMachineDef> {
+    let (full_path, text) = read_in(base_dir, path)?;
+    if !files.contains(&full_path) {
+        files.push(full_path.clone());
+    }
+    let canonical = Path::new(&full_path)
+        .canonicalize()
+        .map_err(|error| Error::new(path.span(), format!("cannot read {full_path}: {error}")))?;
+    let cycle = INCLUDES.with(|includes| {
+        let includes = includes.borrow();
+        let start = includes.iter().position(|file| *file == canonical)?;
+        let files: Vec<_> = includes[start..]
+            .iter()
+            .chain([&canonical])
+            .map(|file| file.display().to_string())
+            .collect();
+        Some(files.join(" -> "))
+    });
+    if let Some(cycle) = cycle {
+        return Err(Error::new(
+            path.span(),
+            format!("{full_path}: include cycle: {cycle}"),
+        ));
+    }
+
+    INCLUDES.with(|includes| includes.borrow_mut().push(canonical));
+    let result = parse_dsl(&full_path, &text, path, files);
+    INCLUDES.with(|includes| includes.borrow_mut().pop());
+    let mut def = result?;
+    def.source_files.insert(0, full_path);
+    Ok(def)
+}
+
+/// A definition loaded from another file: `include = "path"`.
+pub struct Include(pub LitStr);
+
+impl Parse for Include {
+    fn parse(input: ParseStream) -> Result<Self> {
+        input.parse::<kw::include>()?;
+        input.parse::<Token![=]>()?;
+        Ok(Self(input.parse()?))
+    }
+}
+
+fn parse_dsl(
+    full_path: &str,
+    text: &str,
+    path: &LitStr,
+    files: &mut Vec<String>,
+) -> Result<StateMachineDef> {
+    // The errors in the included file are already located.
+    if let Ok(Include(included)) = syn::parse_str(text) {
+        return load_dsl_tracked_in(&base_dir(&included)?, &included, files);
+    }
+    syn::parse_str::<StateMachineDef>(text).map_err(|error| {
+        let (offset, error) = locate(text).unwrap_or((0, error));
+        let (line, column) = position(text, offset);
+        Error::new(path.span(), format!("{full_path}:{line}:{column}: {error}"))
+    })
+}
+
+/// Find the first invalid part of the definition. Outputs its offset in the
+/// text along with the error.
+fn locate(text: &str) -> Option<(usize, Error)> {
+    let mut parts = split(text).into_iter();
+    let header = parts.next()?;
+    if let Err(error) = syn::parse_str::<StateMachineDef>(&text[header.clone()]) {
+        return Some((start_of_tokens(text, header.start), error));
+    }
+    parts
+        .filter(|part| !text[part.clone()].trim().is_empty())
+        .find_map(|part| {
+            syn::parse_str::<TransitionDef>(&text[part.clone()])
+                .err()
+                .map(|error| (start_of_tokens(text, part.start), error))
+        })
+}
+
+/// Split the definition into the header (up to the initial state) and the
+/// transitions separated by commas at the top level.
+fn split(text: &str) -> Vec<std::ops::Range<usize>> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut depth = 0usize;
+    let mut in_header = true;
+    let mut chars = text.char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            '/' if chars.next_if(|&(_, c)| c == '/').is_some() => {
+                while chars.next_if(|&(_, c)| c != '\n').is_some() {}
+            }
+            '/' if chars.next_if(|&(_, c)| c == '*').is_some() => {
+                while let Some((_, c)) = chars.next() {
+                    if c == '*' && chars.next_if(|&(_, c)| c == '/').is_some() {
+                        break;
+                    }
+                }
+            }
+            '"' => {
+                while let Some((_, c)) = chars.next() {
+                    match c {
+                        '\\' => {
+                            chars.next();
+                        }
+                        '"' => break,
+                        _ => {}
+                    }
+                }
+            }
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => {
+                depth = depth.saturating_sub(1);
+                if in_header && c == ')' && depth == 0 {
+                    parts.push(start..offset + 1);
+                    start = offset + 1;
+                    in_header = false;
+                }
+            }
+            ',' if depth == 0 && !in_header => {
+                parts.push(start..offset);
+                start = offset + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(start..text.len());
+    parts
+}
+
+/// Skip the whitespace and the comments preceding the tokens starting at the
+/// given offset.
+fn start_of_tokens(text: &str, mut offset: usize) -> usize {
+    loop {
+        let rest = &text[offset..];
+        let trimmed = rest.trim_start();
+        offset += rest.len() - trimmed.len();
+        if trimmed.starts_with("//") {
+            offset += trimmed.find('\n').unwrap_or(trimmed.len());
+        } else if trimmed.starts_with("/*") {
+            offset += trimmed.find("*/").map_or(trimmed.len(), |end| end + 2);
+        } else {
+            return offset;
+        }
+    }
+}
+
+/// The line and the column (both starting from 1) at the given offset.
+fn position(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset];
+    let line = before.matches('\n').count() + 1;
+    let column = before
+        .rfind('\n')
+        .map_or(before, |newline| &before[newline + 1..])
+        .chars()
+        .count()
+        + 1;
+    (line, column)
+}
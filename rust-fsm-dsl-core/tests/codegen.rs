@@ -0,0 +1,150 @@
+use quote::quote;
+use rust_fsm_dsl_core::{codegen, parser::StateMachineDef};
+
+fn generate(tokens: proc_macro2::TokenStream) -> syn::Result<proc_macro2::TokenStream> {
+    let def: StateMachineDef = syn::parse2(tokens)?;
+    codegen::generate(&def, false)
+}
+
+#[test]
+fn incomplete_machine() {
+    let error = generate(quote! {
+        #[state_machine(complete)]
+        door(Open)
+
+        Open(Close) => Closed,
+        Closed(Open) => Open,
+        Closed(Lock) => Locked,
+        Locked(Unlock) => Closed,
+    })
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "rust-fsm: the machine is not complete, missing transitions: `Closed(Close)`, \
+         `Closed(Unlock)`, \
+         `Locked(Close)`, `Locked(Lock)`, `Locked(Open)`, `Open(Lock)`, `Open(Open)`, \
+         `Open(Unlock)`"
+    );
+
+    // Any default policy handles the missing transitions.
+    generate(quote! {
+        #[state_machine(complete, default = error)]
+        door(Open)
+
+        Open(Close) => Closed,
+        Closed(Open) => Open,
+    })
+    .unwrap();
+}
+
+#[test]
+fn invalid_default_policy() {
+    let error = generate(quote! {
+        #[state_machine(default = retry)]
+        door(Open)
+
+        Open(Close) => Closed,
+    })
+    .unwrap_err();
+    assert_eq!(error.to_string(), "expected `ignore`, `self` or `error`");
+}
+
+#[test]
+fn empty_outputs() {
+    let error = generate(quote! {
+        door(Open)
+
+        Open(Close) => Closed [],
+    })
+    .unwrap_err();
+    assert_eq!(error.to_string(), "expected an output");
+}
+
+#[test]
+fn typestate_clashes() {
+    let error = generate(quote! {
+        #[state_machine(typestate)]
+        door(Open)
+
+        Open(Close) => Machine,
+    })
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "rust-fsm: the state `Machine` clashes with an item of the typestate API"
+    );
+
+    let error = generate(quote! {
+        #[state_machine(typestate)]
+        door(Open)
+
+        Open(New) => Closed,
+    })
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "rust-fsm: the input `New` of the initial state clashes with `Machine::new` of the \
+         typestate API"
+    );
+
+    // Other states may have such an input.
+    generate(quote! {
+        #[state_machine(typestate)]
+        door(Open)
+
+        Open(Close) => Closed,
+        Closed(New) => Open,
+    })
+    .unwrap();
+}
+
+#[test]
+fn atomic_width() {
+    let small = generate(quote! {
+        door(Open)
+
+        Open(Close) => Closed,
+    })
+    .unwrap()
+    .to_string();
+    assert!(small.contains("type Atomic = :: core :: sync :: atomic :: AtomicU8"));
+
+    let transitions: Vec<_> = (0..300)
+        .map(|i| format!("S{i}(Next) => S{}", i + 1))
+        .collect();
+    let large = generate(
+        format!("counter(S0) {}", transitions.join(","))
+            .parse()
+            .unwrap(),
+    )
+    .unwrap()
+    .to_string();
+    assert!(large.contains("type Atomic = :: core :: sync :: atomic :: AtomicU32"));
+}
+
+#[test]
+fn invalid_backend() {
+    let error = generate(quote! {
+        #[state_machine(backend = "jump")]
+        door(Open)
+
+        Open(Close) => Closed,
+    })
+    .unwrap_err();
+    assert_eq!(error.to_string(), "expected `\"match\"` or `\"table\"`");
+}
+
+#[test]
+fn generic_typestate() {
+    let error = generate(quote! {
+        #[state_machine(typestate)]
+        door<T>(Open)
+
+        Open(Close) => Closed,
+    })
+    .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "rust-fsm: the typestate API is not supported for generic machines"
+    );
+}
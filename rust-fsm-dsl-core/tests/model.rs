@@ -0,0 +1,364 @@
+use quote::quote;
+use rust_fsm_dsl_core::{
+    check::{self, Issue},
+    diagram,
+    model::{DefaultPolicy, Machine, StateKind, Transition},
+    parser::StateMachineDef,
+};
+
+fn transition(from: &str, input: &str, to: &str, output: Option<&str>) -> Transition {
+    Transition::new(from, input, to).with_outputs(output)
+}
+
+#[test]
+fn parse_str() {
+    let machine = Machine::parse_str(
+        r#"
+        /// A dummy implementation of the Circuit Breaker pattern.
+        #[derive(Debug)]
+        #[state_machine(export(plantuml))]
+        pub(crate) circuit_breaker(Closed)
+
+        Closed(Unsuccessful) => Open [SetupTimer],
+        Open(TimerTriggered) => HalfOpen,
+        HalfOpen => {
+            Successful => Closed,
+            Unsuccessful => Open [SetupTimer]
+        }
+        "#,
+    )
+    .unwrap();
+
+    let mut expected = Machine::new(
+        "circuit_breaker",
+        "Closed",
+        vec![
+            transition("Closed", "Unsuccessful", "Open", Some("SetupTimer")),
+            transition("Open", "TimerTriggered", "HalfOpen", None),
+            transition("HalfOpen", "Successful", "Closed", None),
+            transition("HalfOpen", "Unsuccessful", "Open", Some("SetupTimer")),
+        ],
+    );
+    expected.visibility = "pub(crate)".to_string();
+    expected.doc = vec![" A dummy implementation of the Circuit Breaker pattern.".to_string()];
+    expected.attributes = vec!["derive(Debug)".to_string()];
+    expected.options.export_plantuml = true;
+    assert_eq!(machine, expected);
+    assert_eq!(machine.states, ["Closed", "HalfOpen", "Open"]);
+    assert_eq!(
+        machine.inputs,
+        ["Successful", "TimerTriggered", "Unsuccessful"]
+    );
+    assert_eq!(machine.outputs, ["SetupTimer"]);
+    assert!(machine.state_info.is_empty());
+}
+
+#[test]
+fn from_tokens() {
+    let machine = Machine::from_tokens(quote! {
+        #[state_machine(input(crate::Input))]
+        door(Open)
+
+        Open(Key) => Closed,
+        Closed(Key) => Open,
+    })
+    .unwrap();
+    assert_eq!(machine.input_type.as_deref(), Some("crate::Input"));
+    assert_eq!(machine.states, ["Closed", "Open"]);
+
+    let error = Machine::from_tokens(quote!(door(Open) Open(Key) Closed)).unwrap_err();
+    assert_eq!(error.to_string(), "expected `=>`");
+}
+
+#[test]
+fn built_directly() {
+    let machine = Machine::new(
+        "door",
+        "Open",
+        vec![
+            transition("Open", "Key", "Closed", None),
+            transition("Closed", "Key", "Open", Some("Locked")),
+            transition("Closed", "Key", "Broken", None),
+        ],
+    );
+    assert_eq!(machine.states, ["Broken", "Closed", "Open"]);
+    assert_eq!(machine.inputs, ["Key"]);
+    assert_eq!(machine.outputs, ["Locked"]);
+    assert_eq!(machine.final_states().collect::<Vec<_>>(), ["Broken"]);
+    assert_eq!(machine.effective_transitions().count(), 2);
+    assert_eq!(
+        diagram::plantuml(&machine),
+        "@startuml\n\
+         [*] --> Open\n\
+         Open --> Closed : Key\n\
+         Closed --> Open : Key / Locked\n\
+         Closed --> Broken : Key\n\
+         Broken --> [*]\n\
+         @enduml"
+    );
+    assert_eq!(
+        check::check(&machine),
+        [
+            Issue::Unreachable {
+                state: "Broken".to_string()
+            },
+            Issue::Nondeterministic {
+                state: "Closed".to_string(),
+                input: "Key".to_string()
+            },
+        ]
+    );
+}
+
+#[test]
+fn transition_builder() {
+    let transition = Transition::new("Closed", "Open", "Opened")
+        .with_outputs(["Creak", "Bang"])
+        .with_doc([" Opens the door."]);
+    assert_eq!(
+        (
+            transition.from.as_str(),
+            transition.input.as_str(),
+            transition.to.as_str()
+        ),
+        ("Closed", "Open", "Opened")
+    );
+    assert_eq!(transition.outputs, ["Creak", "Bang"]);
+    assert_eq!(transition.doc, [" Opens the door."]);
+    assert_eq!(
+        Transition::new("Closed", "Open", "Opened"),
+        Transition::new("Closed", "Open", "Opened").with_outputs(None::<String>)
+    );
+}
+
+#[test]
+fn equivalence_classes() {
+    let machine = Machine::new(
+        "traffic",
+        "Green",
+        vec![
+            transition("Green", "Tick", "Yellow", None),
+            transition("Yellow", "Tick", "Red", None),
+            transition("Red", "Tick", "Green", None),
+        ],
+    );
+    // Without outputs all states behave identically.
+    assert_eq!(machine.equivalence_classes(), [0, 0, 0]);
+
+    let mut machine = machine;
+    machine.transitions[2].outputs = vec!["Go".to_string()];
+    machine.outputs = vec!["Go".to_string()];
+    assert_eq!(machine.equivalence_classes(), [0, 1, 2]);
+}
+
+#[test]
+fn state_descriptions() {
+    let machine = Machine::parse_str(
+        r#"
+        job(Queued)
+
+        /// Waiting for a worker.
+        Queued => {
+            /// A worker picked the job up.
+            Start => Running,
+            Cancel => Cancelled
+        },
+        #[state(group(Active))]
+        Running => {
+            Finish => Done,
+            Crash => Failed [Alert]
+        },
+        /// The job is retried later.
+        #[state(group(Active))]
+        Running(Pause) => Paused,
+        #[state(group(Active))]
+        Paused(Resume) => Running,
+        /// The job completed.
+        #[state(final)]
+        Done,
+        #[state(final)]
+        Cancelled,
+        #[state(error)]
+        Failed,
+        "#,
+    )
+    .unwrap();
+
+    assert_eq!(machine.state_info("Queued").doc, [" Waiting for a worker."]);
+    assert_eq!(
+        machine.state_info("Running").group.as_deref(),
+        Some("Active")
+    );
+    assert_eq!(machine.state_info("Done").kind, StateKind::Final);
+    assert_eq!(machine.state_info("Failed").kind, StateKind::Error);
+    assert_eq!(machine.transitions[0].doc, [" A worker picked the job up."]);
+    assert_eq!(machine.transitions[4].doc, [" The job is retried later."]);
+    assert_eq!(machine.state_info("Running").doc, Vec::<String>::new());
+
+    assert_eq!(
+        diagram::mermaid(&machine),
+        "stateDiagram-v2
+    state Active {
+        Paused
+        Running
+        Running --> Paused: Pause
+        Paused --> Running: Resume
+        note right of Running
+            Pause: The job is retried later.
+        end note
+    }
+    [*] --> Queued
+    Queued --> Running: Start
+    Queued --> Cancelled: Cancel
+    Running --> Done: Finish
+    Running --> Failed: Crash [Alert]
+    Cancelled --> [*]
+    Done --> [*]
+    note right of Done
+        The job completed.
+    end note
+    note right of Queued
+        Waiting for a worker.
+        Start: A worker picked the job up.
+    end note
+    classDef finalState fill:#d4edda,stroke:#28a745
+    class Cancelled,Done finalState
+    classDef errorState fill:#f8d7da,stroke:#dc3545
+    class Failed errorState
+"
+    );
+    assert!(diagram::scxml(&machine).contains("<final id=\"Done\"/>"));
+
+    // The descriptions survive the conversion back into the definition.
+    let def = StateMachineDef::try_from(&machine).unwrap();
+    assert_eq!(Machine::from(&def), machine);
+}
+
+#[test]
+fn conflicting_state_attributes() {
+    let error = Machine::parse_str(
+        "door(Open) #[state(final)] Open(Close) => Closed, #[state(error)] Open",
+    )
+    .unwrap_err();
+    assert_eq!(error.to_string(), "a state cannot be both final and error");
+
+    let error =
+        Machine::parse_str("door(Open) #[state(group(Closed))] Open(Close) => Closed").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "the name of a group cannot be the name of a state"
+    );
+
+    let error =
+        Machine::parse_str("door(Open) #[derive(Debug)] Open(Close) => Closed").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "only doc comments and `#[state(...)]` are allowed on states"
+    );
+}
+
+#[test]
+fn default_policy() {
+    let machine = Machine::parse_str(
+        "#[state_machine(complete, default = self)]
+        door(Open)
+
+        Open(Close) => Closed,
+        Closed(Open) => Open,",
+    )
+    .unwrap();
+    assert!(machine.options.complete);
+    assert_eq!(machine.options.default, Some(DefaultPolicy::SelfLoop));
+    assert_eq!(
+        machine.default_transitions(),
+        [
+            transition("Closed", "Close", "Closed", None),
+            transition("Open", "Open", "Open", None),
+        ]
+    );
+    assert_eq!(machine.diagram_transitions().len(), 4);
+    // The missing transitions are handled by the policy.
+    assert!(check::check(&machine).is_empty());
+
+    let def = StateMachineDef::try_from(&machine).unwrap();
+    assert_eq!(Machine::from(&def), machine);
+}
+
+#[test]
+fn state_outputs() {
+    let machine = Machine::parse_str(
+        "light(Off)
+
+        Off [Dark](Toggle) => On,
+        On [Bright] => {
+            Toggle => Off [Click]
+        },",
+    )
+    .unwrap();
+    assert_eq!(machine.state_info("On").output.as_deref(), Some("Bright"));
+    assert_eq!(machine.outputs, ["Bright", "Click", "Dark"]);
+    assert_eq!(
+        machine.transition_outputs(&machine.transitions[1]),
+        ["Click", "Dark"]
+    );
+
+    let def = StateMachineDef::try_from(&machine).unwrap();
+    assert_eq!(Machine::from(&def), machine);
+
+    let error = Machine::parse_str("light(Off) Off [Dark, Black](Toggle) => On").unwrap_err();
+    assert_eq!(error.to_string(), "a state can have only one output");
+
+    let error = Machine::parse_str("light(Off) Off [Dark](Toggle) => On, Off [Black]").unwrap_err();
+    assert_eq!(error.to_string(), "the state already has the `Dark` output");
+}
+
+#[test]
+fn generics() {
+    let machine = Machine::parse_str(
+        "transfer<'a, T: Clone, const N: usize>(Idle) where T: Default
+
+        Idle(Begin) => Sending,",
+    )
+    .unwrap();
+    assert_eq!(machine.generics, "<'a, T: Clone, const N: usize>");
+    assert_eq!(machine.where_clause, "where T: Default");
+    assert_eq!(
+        machine.transitions,
+        [transition("Idle", "Begin", "Sending", None)]
+    );
+
+    let def = StateMachineDef::try_from(&machine).unwrap();
+    assert_eq!(Machine::from(&def), machine);
+}
+
+#[test]
+fn source_text() {
+    // The paths, attributes and generics are written as in the source code
+    // regardless of the formatting of the definition.
+    let machine = Machine::parse_str(
+        "#[cfg_attr(test, derive(Debug , Clone))]
+        #[state_machine(input(crate :: machines :: Input))]
+        pub(in crate::machines) transfer<'a, T: Fn(&'a [u8; 4]) -> Option<Vec<u8>>, const N: usize>(Idle)
+        where T: Default + ?Sized, Vec<T>: Clone
+
+        Idle(Begin) => Sending,",
+    )
+    .unwrap();
+    assert_eq!(machine.attributes, ["cfg_attr(test, derive(Debug, Clone))"]);
+    assert_eq!(
+        machine.input_type.as_deref(),
+        Some("crate::machines::Input")
+    );
+    assert_eq!(machine.visibility, "pub(in crate::machines)");
+    assert_eq!(
+        machine.generics,
+        "<'a, T: Fn(&'a [u8; 4]) -> Option<Vec<u8>>, const N: usize>"
+    );
+    assert_eq!(
+        machine.where_clause,
+        "where T: Default + ?Sized, Vec<T>: Clone"
+    );
+
+    let def = StateMachineDef::try_from(&machine).unwrap();
+    assert_eq!(Machine::from(&def), machine);
+}
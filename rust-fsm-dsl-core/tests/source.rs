@@ -0,0 +1,64 @@
+use proc_macro2::Span;
+use rust_fsm_dsl_core::source::{load_dsl, load_dsl_tracked_in};
+use std::path::Path;
+use syn::LitStr;
+
+fn load_error(path: &str) -> String {
+    match load_dsl(&LitStr::new(path, Span::call_site())) {
+        Ok(_) => panic!("{path} was loaded"),
+        Err(error) => error.to_string(),
+    }
+}
+
+fn fixture(path: &str) -> String {
+    format!("{}/tests/fsm/{path}", env!("CARGO_MANIFEST_DIR"))
+}
+
+#[test]
+fn error_in_included_file() {
+    let error = load_error("tests/fsm/outer.fsm");
+    // The nested path is relative to the including file and the position is
+    // the one of the invalid transition in that file.
+    assert!(
+        error.starts_with(&format!("{}:4:1: ", fixture("nested/broken.fsm"))),
+        "unexpected error: {error}"
+    );
+}
+
+#[test]
+fn include_cycle() {
+    let error = load_error("tests/fsm/cycle_a.fsm");
+    assert!(
+        error.ends_with(&format!(
+            "include cycle: {} -> {} -> {}",
+            fixture("cycle_a.fsm"),
+            fixture("cycle_b.fsm"),
+            fixture("cycle_a.fsm")
+        )),
+        "unexpected error: {error}"
+    );
+
+    let error = load_error("tests/fsm/self.fsm");
+    assert!(
+        error.ends_with(&format!(
+            "include cycle: {} -> {}",
+            fixture("self.fsm"),
+            fixture("self.fsm")
+        )),
+        "unexpected error: {error}"
+    );
+}
+
+#[test]
+fn files_read_before_error() {
+    let mut files = Vec::new();
+    let path = LitStr::new("outer.fsm", Span::call_site());
+    let base_dir = fixture("");
+    assert!(load_dsl_tracked_in(Path::new(&base_dir), &path, &mut files).is_err());
+    assert_eq!(files, [fixture("outer.fsm"), fixture("nested/broken.fsm")]);
+
+    let mut files = Vec::new();
+    let path = LitStr::new("cycle_a.fsm", Span::call_site());
+    assert!(load_dsl_tracked_in(Path::new(&base_dir), &path, &mut files).is_err());
+    assert_eq!(files, [fixture("cycle_a.fsm"), fixture("cycle_b.fsm")]);
+}
@@ -0,0 +1,117 @@
+//! Detection of equivalent states of finite state machines using Hopcroft's
+//! algorithm. The machines are described by the indices of their states,
+//! inputs and outputs. This is the implementation shared by the runtime
+//! minimization in `rust_fsm::minimize` and the macros.
+
+use std::{collections::HashMap, hash::Hash};
+
+/// Compute the classes of equivalent states. `transition` and `outputs` give
+/// the next state and the outputs of the transition for a state and an input,
+/// `state_output` the output of a state itself. Outputs the class index for
+/// every state, with classes numbered in the order of their first appearance.
+pub fn equivalence_classes<O: Hash + Eq>(
+    num_states: usize,
+    num_inputs: usize,
+    transition: impl Fn(usize, usize) -> Option<usize>,
+    outputs: impl Fn(usize, usize) -> O,
+    state_output: impl Fn(usize) -> Option<usize>,
+) -> Vec<usize> {
+    // States are initially split by their own outputs, the set of inputs they
+    // accept and the outputs they give on those inputs.
+    let mut signatures = HashMap::new();
+    let initial = (0..num_states)
+        .map(|state| {
+            let signature: Vec<_> = (0..num_inputs)
+                .map(|input| (transition(state, input).is_some(), outputs(state, input)))
+                .collect();
+            let next = signatures.len();
+            *signatures
+                .entry((state_output(state), signature))
+                .or_insert(next)
+        })
+        .collect();
+
+    refine(num_states, num_inputs, initial, transition)
+}
+
+/// Hopcroft's partition refinement. Takes the initial partition of states as a
+/// class index for every state and refines it until the transition function is
+/// compatible with it. Outputs the class index for every state, with classes
+/// renumbered in the order of their first appearance.
+fn refine(
+    num_states: usize,
+    num_inputs: usize,
+    initial: Vec<usize>,
+    transition: impl Fn(usize, usize) -> Option<usize>,
+) -> Vec<usize> {
+    // preimages[input][state] lists the states moving to `state` on `input`.
+    let mut preimages = vec![vec![Vec::new(); num_states]; num_inputs];
+    for state in 0..num_states {
+        for (input, preimage) in preimages.iter_mut().enumerate() {
+            if let Some(next) = transition(state, input) {
+                preimage[next].push(state);
+            }
+        }
+    }
+
+    let num_initial = initial.iter().max().map_or(0, |max| max + 1);
+    let mut blocks = vec![Vec::new(); num_initial];
+    for (state, &block) in initial.iter().enumerate() {
+        blocks[block].push(state);
+    }
+    let mut block_of = initial;
+
+    let mut waiting: Vec<(usize, usize)> = (0..blocks.len())
+        .flat_map(|block| (0..num_inputs).map(move |input| (block, input)))
+        .collect();
+
+    while let Some((splitter, input)) = waiting.pop() {
+        let mut marked = vec![false; num_states];
+        let mut touched = Vec::new();
+        for &target in &blocks[splitter] {
+            for &state in &preimages[input][target] {
+                if !marked[state] {
+                    marked[state] = true;
+                    touched.push(block_of[state]);
+                }
+            }
+        }
+        touched.sort_unstable();
+        touched.dedup();
+
+        for block in touched {
+            let (inside, outside): (Vec<_>, Vec<_>) =
+                blocks[block].iter().partition(|&&state| marked[state]);
+            if outside.is_empty() {
+                continue;
+            }
+            // The smaller half becomes a new block and is always enqueued:
+            // either the old block is still waiting and both halves must be
+            // processed, or processing the smaller half suffices.
+            let (kept, moved) = if inside.len() >= outside.len() {
+                (inside, outside)
+            } else {
+                (outside, inside)
+            };
+            let new_block = blocks.len();
+            for &state in &moved {
+                block_of[state] = new_block;
+            }
+            blocks[block] = kept;
+            blocks.push(moved);
+            waiting.extend((0..num_inputs).map(|c| (new_block, c)));
+        }
+    }
+
+    let mut renumbered = vec![None; blocks.len()];
+    let mut next = 0;
+    block_of
+        .into_iter()
+        .map(|block| {
+            *renumbered[block].get_or_insert_with(|| {
+                next += 1;
+                next - 1
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,40 @@
+use rust_fsm_minimize::equivalence_classes;
+
+/// A door that can be opened and closed with a key or broken in either state.
+/// `Open` (0) and `Closed` (1) are equivalent, `Broken` (2) accepts nothing.
+const TRANSITIONS: [[Option<usize>; 2]; 3] = [[Some(1), Some(2)], [Some(0), Some(2)], [None, None]];
+
+#[test]
+fn equivalent_states() {
+    let classes = equivalence_classes(
+        3,
+        2,
+        |state, input| TRANSITIONS[state][input],
+        |_, _| (),
+        |_| None,
+    );
+    assert_eq!(classes, [0, 0, 1]);
+}
+
+#[test]
+fn split_by_outputs() {
+    // The key gives an output only in the `Closed` state.
+    let classes = equivalence_classes(
+        3,
+        2,
+        |state, input| TRANSITIONS[state][input],
+        |state, input| (state == 1 && input == 0).then_some(0),
+        |_| None,
+    );
+    assert_eq!(classes, [0, 1, 2]);
+
+    // So does the state output.
+    let classes = equivalence_classes(
+        3,
+        2,
+        |state, input| TRANSITIONS[state][input],
+        |_, _| (),
+        |state| (state == 0).then_some(0),
+    );
+    assert_eq!(classes, [0, 1, 2]);
+}
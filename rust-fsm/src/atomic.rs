@@ -0,0 +1,226 @@
+//! A lock-free state machine for fieldless states.
+//!
+//! [`AtomicStateMachine`] stores the state as an integer in an atomic and
+//! performs the transitions with a compare-and-swap loop, so the inputs can be
+//! consumed through a shared reference without locking. It is available in
+//! `no_std` environments.
+//!
+//! The `state_machine` macro implements [`AtomicState`] for the generated
+//! `State` enums, storing them in an `AtomicU8` (or in an `AtomicU32` for more
+//! than 256 states).
+
+use crate::{Outputs, StateMachine, StateMachineImpl, TransitionImpossibleError};
+#[cfg(target_has_atomic = "32")]
+use core::sync::atomic::AtomicU32;
+use core::{
+    fmt,
+    marker::PhantomData,
+    sync::atomic::{AtomicU8, Ordering},
+};
+
+/// A state that can be stored in an [`AtomicStateMachine`]: typically a
+/// fieldless enum. The conversions must be lossless: `from_bits(state.to_bits())`
+/// gives the same state back.
+pub trait AtomicState: Sized {
+    /// The atomic integer the state is stored in: `AtomicU8` or `AtomicU32`.
+    type Atomic: AtomicBits;
+    /// Convert the state into an integer.
+    fn to_bits(&self) -> <Self::Atomic as AtomicBits>::Bits;
+    /// Convert an integer back into a state. Outputs `None` for integers not
+    /// produced by `to_bits`.
+    fn from_bits(bits: <Self::Atomic as AtomicBits>::Bits) -> Option<Self>;
+}
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// An atomic integer type a state can be stored in. Implemented for `AtomicU8`
+/// and `AtomicU32`.
+pub trait AtomicBits: sealed::Sealed {
+    /// The integer type stored in the atomic.
+    type Bits: Copy + Eq;
+    #[doc(hidden)]
+    fn new(bits: Self::Bits) -> Self;
+    #[doc(hidden)]
+    fn load(&self, order: Ordering) -> Self::Bits;
+    #[doc(hidden)]
+    fn compare_exchange_weak(
+        &self,
+        current: Self::Bits,
+        new: Self::Bits,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<Self::Bits, Self::Bits>;
+    #[doc(hidden)]
+    fn into_inner(self) -> Self::Bits;
+}
+
+macro_rules! atomic_bits {
+    ($atomic:ty, $bits:ty) => {
+        impl sealed::Sealed for $atomic {}
+
+        impl AtomicBits for $atomic {
+            type Bits = $bits;
+
+            fn new(bits: Self::Bits) -> Self {
+                <$atomic>::new(bits)
+            }
+
+            fn load(&self, order: Ordering) -> Self::Bits {
+                <$atomic>::load(self, order)
+            }
+
+            fn compare_exchange_weak(
+                &self,
+                current: Self::Bits,
+                new: Self::Bits,
+                success: Ordering,
+                failure: Ordering,
+            ) -> Result<Self::Bits, Self::Bits> {
+                <$atomic>::compare_exchange_weak(self, current, new, success, failure)
+            }
+
+            fn into_inner(self) -> Self::Bits {
+                <$atomic>::into_inner(self)
+            }
+        }
+    };
+}
+
+atomic_bits!(AtomicU8, u8);
+#[cfg(target_has_atomic = "32")]
+atomic_bits!(AtomicU32, u32);
+
+/// A wrapper around the state of a machine that consumes inputs via `&self`
+/// without locking.
+///
+/// The transition and output functions may be called more than once for the
+/// same input when other threads change the state concurrently, so they should
+/// not have side effects.
+pub struct AtomicStateMachine<T: StateMachineImpl>
+where
+    T::State: AtomicState,
+{
+    state: <T::State as AtomicState>::Atomic,
+    _impl: PhantomData<fn() -> T>,
+}
+
+impl<T> AtomicStateMachine<T>
+where
+    T: StateMachineImpl,
+    T::State: AtomicState,
+{
+    /// Create a new instance of this wrapper which encapsulates the initial
+    /// state.
+    pub fn new() -> Self {
+        Self::from_state(T::INITIAL_STATE)
+    }
+
+    /// Create a new instance of this wrapper which encapsulates the given
+    /// state.
+    pub fn from_state(state: T::State) -> Self {
+        Self {
+            state: AtomicBits::new(state.to_bits()),
+            _impl: PhantomData,
+        }
+    }
+
+    /// Consumes the provided input, gives an output and performs a state
+    /// transition. If a state transition with the current state and the
+    /// provided input is not allowed, returns an error.
+    pub fn consume(
+        &self,
+        input: &T::Input,
+    ) -> Result<Option<T::Output>, TransitionImpossibleError> {
+        let state = self.transition(input)?;
+        Ok(T::output(&state, input))
+    }
+
+    /// Consumes the provided input, performs a state transition and gives an
+    /// iterator over all outputs of the transition. If a state transition with
+    /// the current state and the provided input is not allowed, returns an
+    /// error.
+    pub fn consume_outputs<'a>(
+        &self,
+        input: &'a T::Input,
+    ) -> Result<Outputs<'a, T>, TransitionImpossibleError> {
+        let state = self.transition(input)?;
+        Ok(Outputs {
+            state,
+            input,
+            index: 0,
+        })
+    }
+
+    /// Returns the current state.
+    pub fn state(&self) -> T::State {
+        Self::decode(self.state.load(Ordering::Acquire))
+    }
+
+    /// Returns the output of the current state, see
+    /// [`StateMachineImpl::state_output`].
+    pub fn current_output(&self) -> Option<T::Output> {
+        T::state_output(&self.state())
+    }
+
+    /// Unwraps the machine.
+    pub fn into_inner(self) -> StateMachine<T> {
+        StateMachine::from_state(Self::decode(self.state.into_inner()))
+    }
+
+    /// Performs the transition on the provided input and gives the state it
+    /// was performed from.
+    fn transition(&self, input: &T::Input) -> Result<T::State, TransitionImpossibleError> {
+        let mut current = self.state.load(Ordering::Acquire);
+        loop {
+            let state = Self::decode(current);
+            let next = T::transition(&state, input).ok_or(TransitionImpossibleError)?;
+            match self.state.compare_exchange_weak(
+                current,
+                next.to_bits(),
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => return Ok(state),
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    fn decode(bits: <<T::State as AtomicState>::Atomic as AtomicBits>::Bits) -> T::State {
+        T::State::from_bits(bits).expect("the atomic holds a value produced by `to_bits`")
+    }
+}
+
+impl<T> Default for AtomicStateMachine<T>
+where
+    T: StateMachineImpl,
+    T::State: AtomicState,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> From<StateMachine<T>> for AtomicStateMachine<T>
+where
+    T: StateMachineImpl,
+    T::State: AtomicState,
+{
+    fn from(machine: StateMachine<T>) -> Self {
+        Self::from_state(machine.state)
+    }
+}
+
+impl<T> fmt::Debug for AtomicStateMachine<T>
+where
+    T: StateMachineImpl,
+    T::State: AtomicState + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("AtomicStateMachine")
+            .field("state", &self.state())
+            .finish()
+    }
+}
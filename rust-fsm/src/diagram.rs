@@ -0,0 +1,90 @@
+//! Rendering of state diagrams at runtime.
+//!
+//! Unlike the `MERMAID` constant generated by the `state_machine` macro, the
+//! diagrams here are built from the reflection of a machine, so they work for
+//! any [`StateMachineReflect`] implementation and can highlight the current
+//! state of a running [`StateMachine`]. Transitions are listed in the order of
+//! the indices of their states and inputs.
+
+use crate::{reflect::TransitionTable, StateMachine, StateMachineReflect};
+use std::fmt::Write;
+
+/// The style applied to the highlighted state.
+const CURRENT_STATE_STYLE: &str = "fill:#fff3cd,stroke:#ffc107,stroke-width:3px";
+
+impl TransitionTable {
+    /// Generate a Mermaid `stateDiagram-v2` diagram of the machine. The
+    /// outputs of the states are shown as `entry / Output` inside them. If
+    /// `current` is set, the state with this index is highlighted with the
+    /// `currentState` class.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `current` is not a valid state index.
+    pub fn mermaid(&self, current: Option<usize>) -> String {
+        let names = self.state_names();
+        let mut diagram = String::from("stateDiagram-v2\n");
+        for (state, name) in names.iter().enumerate() {
+            if let Some(output) = self.state_output(state) {
+                writeln!(
+                    diagram,
+                    "    {name}: entry / {}",
+                    self.output_names()[output]
+                )
+                .unwrap();
+            }
+        }
+        writeln!(diagram, "    [*] --> {}", names[self.initial_state()]).unwrap();
+        for state in 0..self.num_states() {
+            for input in 0..self.num_inputs() {
+                let Some(next) = self.transition(state, input) else {
+                    continue;
+                };
+                write!(
+                    diagram,
+                    "    {} --> {}: {}",
+                    names[state],
+                    names[next],
+                    self.input_names()[input]
+                )
+                .unwrap();
+                // The output of the entered state ends the outputs of the
+                // transition, it is already shown inside the state.
+                let mut outputs = self.outputs(state, input);
+                if let Some(state_output) = self.state_output(next) {
+                    outputs = outputs.strip_suffix(&[state_output]).unwrap_or(outputs);
+                }
+                let outputs: Vec<_> = outputs
+                    .iter()
+                    .map(|&output| self.output_names()[output])
+                    .collect();
+                if !outputs.is_empty() {
+                    write!(diagram, " [{}]", outputs.join(", ")).unwrap();
+                }
+                diagram.push('\n');
+            }
+        }
+        if let Some(current) = current {
+            writeln!(diagram, "    classDef currentState {CURRENT_STATE_STYLE}").unwrap();
+            writeln!(diagram, "    class {} currentState", names[current]).unwrap();
+        }
+        diagram
+    }
+}
+
+/// Generate a Mermaid diagram of the given state machine.
+pub fn mermaid<T: StateMachineReflect>() -> String {
+    TransitionTable::of::<T>().mermaid(None)
+}
+
+impl<T> StateMachine<T>
+where
+    T: StateMachineReflect,
+{
+    /// Generate a Mermaid diagram of this machine with the current state
+    /// highlighted. The state is not highlighted if it is not known to the
+    /// reflection.
+    pub fn mermaid(&self) -> String {
+        TransitionTable::of::<T>().mermaid(T::state_index(self.state()))
+    }
+}
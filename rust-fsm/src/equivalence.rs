@@ -0,0 +1,193 @@
+//! Equivalence and refinement checking of state machines.
+//!
+//! Machines are compared by their observable behavior: for every sequence of
+//! inputs they must accept or reject the same inputs and give the same
+//! outputs. Inputs and outputs of different machines are matched by their
+//! names, so machines generated by different `state_machine` invocations can be
+//! compared with each other. The comparison is done by exploring the product
+//! of both machines breadth-first, so the reported counterexample is always
+//! one of the shortest.
+
+use crate::reflect::{StateMachineReflect, TransitionTable};
+use std::{collections::VecDeque, error::Error, fmt};
+
+/// How a machine reacts to an input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reaction {
+    /// The transition is impossible.
+    Rejected,
+    /// The transition is performed, giving the listed outputs.
+    Accepted(Vec<&'static str>),
+}
+
+/// A sequence of inputs that distinguishes two machines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Counterexample {
+    /// The names of the inputs. Both machines react identically to all inputs
+    /// but the last one.
+    pub inputs: Vec<&'static str>,
+    /// The reaction of the left machine (the first machine for equivalence and
+    /// the specification for refinement) to the last input.
+    pub left: Reaction,
+    /// The reaction of the right machine (the second machine for equivalence
+    /// and the implementation for refinement) to the last input.
+    pub right: Reaction,
+}
+
+impl Counterexample {
+    /// Convert the input names into the inputs of the given machine. Outputs
+    /// `None` if any of the names is unknown to the machine, since the
+    /// sequence would not reproduce the difference.
+    pub fn inputs_of<T: StateMachineReflect>(&self) -> Option<Vec<T::Input>> {
+        self.inputs
+            .iter()
+            .map(|name| {
+                let index = T::INPUT_NAMES.iter().position(|n| n == name)?;
+                T::input_at(index)
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for Reaction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Rejected => write!(f, "rejected"),
+            Self::Accepted(outputs) => match outputs[..] {
+                [] => write!(f, "accepted without output"),
+                [output] => write!(f, "accepted with output {output}"),
+                _ => write!(f, "accepted with outputs {}", outputs.join(", ")),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Counterexample {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "the machines differ after the inputs [{}]: {} vs {}",
+            self.inputs.join(", "),
+            self.left,
+            self.right
+        )
+    }
+}
+
+impl Error for Counterexample {}
+
+/// Check that two machines have identical observable behavior.
+pub fn check_equivalence<A, B>() -> Result<(), Counterexample>
+where
+    A: StateMachineReflect,
+    B: StateMachineReflect,
+{
+    TransitionTable::of::<A>().check_equivalence(&TransitionTable::of::<B>())
+}
+
+/// Check that `Impl` refines `Spec`: every sequence of inputs accepted by
+/// `Spec` is also accepted by `Impl` with the same outputs. `Impl` is free to
+/// accept more inputs than `Spec`.
+pub fn check_refinement<Spec, Impl>() -> Result<(), Counterexample>
+where
+    Spec: StateMachineReflect,
+    Impl: StateMachineReflect,
+{
+    TransitionTable::of::<Impl>().check_refinement(&TransitionTable::of::<Spec>())
+}
+
+impl TransitionTable {
+    /// Check that this machine and `other` have identical observable behavior.
+    pub fn check_equivalence(&self, other: &TransitionTable) -> Result<(), Counterexample> {
+        compare(self, other, false)
+    }
+
+    /// Check that this machine refines `spec`: every sequence of inputs
+    /// accepted by `spec` is also accepted by this machine with the same
+    /// outputs.
+    pub fn check_refinement(&self, spec: &TransitionTable) -> Result<(), Counterexample> {
+        compare(spec, self, true)
+    }
+
+    fn react(&self, state: usize, input: Option<usize>) -> (Reaction, Option<usize>) {
+        match input.and_then(|input| Some((input, self.transition(state, input)?))) {
+            Some((input, next)) => {
+                let outputs = self
+                    .outputs(state, input)
+                    .iter()
+                    .map(|&output| self.output_names()[output])
+                    .collect();
+                (Reaction::Accepted(outputs), Some(next))
+            }
+            None => (Reaction::Rejected, None),
+        }
+    }
+}
+
+fn compare(
+    left: &TransitionTable,
+    right: &TransitionTable,
+    refinement: bool,
+) -> Result<(), Counterexample> {
+    // The union of both input alphabets with indices into each of them.
+    let mut inputs: Vec<(&'static str, Option<usize>, Option<usize>)> = left
+        .input_names()
+        .iter()
+        .enumerate()
+        .map(|(index, &name)| {
+            let right_index = right.input_names().iter().position(|&n| n == name);
+            (name, Some(index), right_index)
+        })
+        .collect();
+    for (index, &name) in right.input_names().iter().enumerate() {
+        if !left.input_names().contains(&name) {
+            inputs.push((name, None, Some(index)));
+        }
+    }
+
+    // Each visited pair of states remembers the pair it was reached from and
+    // the input it was reached with to restore the path.
+    let mut visited = vec![None; left.num_states() * right.num_states()];
+    let start = (left.initial_state(), right.initial_state());
+    visited[start.0 * right.num_states() + start.1] = Some(None);
+    let mut queue = VecDeque::from([start]);
+
+    while let Some((l, r)) = queue.pop_front() {
+        for &(name, left_input, right_input) in &inputs {
+            let (left_reaction, left_next) = left.react(l, left_input);
+            let (right_reaction, right_next) = right.react(r, right_input);
+
+            let differs = if refinement {
+                left_reaction != Reaction::Rejected && left_reaction != right_reaction
+            } else {
+                left_reaction != right_reaction
+            };
+            if differs {
+                let mut path = vec![name];
+                let mut current = (l, r);
+                while let Some(Some((previous, input))) =
+                    visited[current.0 * right.num_states() + current.1]
+                {
+                    path.push(input);
+                    current = previous;
+                }
+                path.reverse();
+                return Err(Counterexample {
+                    inputs: path,
+                    left: left_reaction,
+                    right: right_reaction,
+                });
+            }
+
+            if let (Some(left_next), Some(right_next)) = (left_next, right_next) {
+                let slot = &mut visited[left_next * right.num_states() + right_next];
+                if slot.is_none() {
+                    *slot = Some(Some(((l, r), name)));
+                    queue.push_back((left_next, right_next));
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
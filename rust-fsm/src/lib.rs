@@ -40,6 +40,9 @@ of state machines:
 ### Non-default
 
 - `diagram` - generate Mermaid state diagrams in the doc strings. See below.
+- `proptest` - `proptest` strategies for input sequences and a model-based
+  testing harness. See below.
+- `quickcheck` - `quickcheck` support for input sequences. See below.
 
 ## Usage in `no_std` environments
 
@@ -132,6 +135,64 @@ attributes (e.g. `derive`, `repr`) are applied to it.
 Within the `state_machine` macro you must define at least one state
 transition.
 
+#### Multiple outputs
+
+A transition can give several outputs, listed in the brackets:
+
+```rust,ignore
+state_machine! {
+    connection(Connecting)
+
+    Connecting(Connected) => Open [CancelTimer, NotifyPeer],
+    Open(Close) => Closed [NotifyPeer],
+}
+```
+
+`consume` gives the first output of the transition, while `consume_outputs`
+gives an iterator over all of them. The iterator does not allocate, so it can be
+used in `no_std` environments:
+
+```rust,ignore
+let mut machine = connection::StateMachine::new();
+for output in machine.consume_outputs(&connection::Input::Connected).unwrap() {
+    // CancelTimer, then NotifyPeer
+}
+```
+
+The outputs are produced by the `nth_output` method of `StateMachineImpl`,
+which defaults to giving the only output of `output`.
+
+#### State outputs (Moore machines)
+
+A state can have an output of its own, given in the brackets after its name.
+The output is given whenever the machine enters the state, after the outputs of
+the transition:
+
+```rust,ignore
+state_machine! {
+    traffic_light(Red)
+
+    Red [Stop] => {
+        Timer => Green
+    },
+    Green [Go](Timer) => Yellow,
+    Yellow [Slow](Timer) => Red,
+}
+```
+
+`current_output` gives the output of the current state, which also covers the
+initial state:
+
+```rust,ignore
+let mut machine = traffic_light::StateMachine::new();
+assert_eq!(machine.current_output(), Some(traffic_light::Output::Stop));
+machine.consume(&traffic_light::Input::Timer).unwrap(); // Some(Go)
+```
+
+The outputs are produced by the `state_output` method of `StateMachineImpl`,
+which defaults to no output. Diagrams show the outputs inside the states as
+`entry / Output`.
+
 #### Visibility
 
 You can specify visibility like this:
@@ -157,7 +218,8 @@ The default visibility is private.
 
 You can supply your own types to use as input, output or state. All of them are
 optional: you can use only one of them or all of them at once if you want to.
-The current limitation is that you have to supply a fully qualified type path.
+The paths are resolved in the scope of the macro invocation, so the types can be
+referred to by the names in scope, `super::` or fully qualified paths.
 
 ```rust,ignore
 use rust_fsm::*;
@@ -179,7 +241,7 @@ pub enum Output {
 }
 
 state_machine! {
-    #[state_machine(input(crate::Input), state(crate::State), output(crate::Output))]
+    #[state_machine(input(Input), state(State), output(crate::Output))]
     circuit_breaker(Closed)
 
     Closed(Unsuccessful) => Open [SetupTimer],
@@ -191,6 +253,45 @@ state_machine! {
 }
 ```
 
+The macro generates a module importing all items of the enclosing one. Hence
+the types declared inside function bodies cannot be used, since the module does
+not see them. The variants used in the definition must be unit variants of the
+types, otherwise the error points at the name of the variant.
+
+#### Generic machines
+
+The machine may have generic parameters and a where clause, which are applied
+to the generated `Impl`, `State`, `Input` and `Output` types and the
+`StateMachine` alias. This way the custom alphabet types may be generic or
+borrow data:
+
+```rust,ignore
+use rust_fsm::*;
+
+pub enum Frame<'a> {
+    Open,
+    Close,
+    Payload(&'a [u8]),
+}
+
+state_machine! {
+    #[state_machine(input(crate::Frame<'a>))]
+    connection<'a, T>(Closed) where T: Clone
+
+    Closed(Open) => Opened [Connected],
+    Opened(Close) => Closed,
+}
+
+let mut machine = connection::StateMachine::<u8>::new();
+machine.consume(&Frame::Open).unwrap();
+```
+
+Like the custom types, the bounds are resolved in the scope of the macro
+invocation. The generated enums get a hidden uninhabited variant using the
+parameters: it may be left out of the matches by value, but the matches by
+reference need a wildcard pattern. The typestate API is not supported for
+generic machines.
+
 #### Diagrams
 
 `state_machine` macro can document your state machines with diagrams. This is
@@ -206,6 +307,403 @@ cargo doc -p doc-example --open
 
 ![image](doc-diagram-example.png)
 
+Every generated module also contains the `MERMAID` constant with the Mermaid
+diagram and the `DOT` constant with the same diagram in the
+[Graphviz][graphviz] DOT format, regardless of the `diagram` feature, so the
+diagrams can be shown by the program itself. In the DOT diagram the initial
+state is pointed to by an arrow from a dot and the states without outgoing
+transitions are drawn as double circles. You can choose which diagrams
+are included into the documentation:
+
+```rust,ignore
+state_machine! {
+    // The default is `diagram(mermaid)`.
+    #[state_machine(diagram(mermaid, dot))]
+    circuit_breaker(Closed)
+    // ...
+}
+```
+
+Note that rustdoc does not render DOT diagrams, they are included as code
+blocks.
+
+The Mermaid diagram can be turned into a design document by describing the
+states and the transitions in the definition:
+
+```rust,ignore
+state_machine! {
+    job(Queued)
+
+    /// Waiting for a worker.
+    Queued => {
+        /// A worker picked the job up.
+        Start => Running,
+        Cancel => Cancelled
+    },
+    #[state(group(Active))]
+    Running => {
+        Finish => Done,
+        Crash => Failed [Alert]
+    },
+    #[state(group(Active))]
+    Paused(Resume) => Running,
+    /// The job completed.
+    #[state(final)]
+    Done,
+    #[state(final)]
+    Cancelled,
+    #[state(error)]
+    Failed,
+}
+```
+
+- Doc comments on a state (in the compact form or on a declaration of a state
+  without transitions) and on the transitions are shown as notes next to the
+  state. In the simple form, `State(Input) => Next`, a doc comment describes the
+  transition. Doc comments on states also document the variants of `State`.
+- `#[state(final)]` and `#[state(error)]` style the state with the
+  `finalState` and `errorState` classes. Final states are also connected to the
+  final pseudostate.
+- `#[state(group(Name))]` draws the state inside the `Name` composite state.
+
+The machine can also be exported as a [PlantUML][plantuml] diagram and as an
+[SCXML][scxml] document (outputs are sent to the code running the machine with
+`<send target="#_parent">`) in the `PLANTUML` and `SCXML` constants
+respectively:
+
+```rust,ignore
+state_machine! {
+    #[state_machine(export(plantuml, scxml))]
+    circuit_breaker(Closed)
+    // ...
+}
+```
+
+With the `std` feature, the diagram of any machine implementing
+`StateMachineReflect` can also be rendered at runtime, highlighting the current
+state of a running machine:
+
+```rust,ignore
+// The diagram of the machine.
+let diagram = rust_fsm::diagram::mermaid::<circuit_breaker::Impl>();
+// The same diagram with the current state highlighted.
+let machine = circuit_breaker::StateMachine::new();
+let diagram = machine.mermaid();
+```
+
+#### Definitions in external files
+
+Large machines can be kept in separate `.fsm` files using the same syntax as
+the contents of the `state_machine` macro. The path is relative to the directory
+containing `Cargo.toml` of the crate:
+
+```rust,ignore
+state_machine!(include = "machines/circuit_breaker.fsm");
+```
+
+where `machines/circuit_breaker.fsm` contains:
+
+```text
+#[derive(Debug)]
+pub circuit_breaker(Closed)
+
+Closed(Unsuccessful) => Open [SetupTimer],
+Open(TimerTriggered) => HalfOpen,
+HalfOpen => {
+    Successful => Closed,
+    Unsuccessful => Open [SetupTimer]
+}
+```
+
+A file may in turn consist of `include = "other.fsm"`, the path being
+relative to the including file. Include cycles are reported as errors. Syntax
+errors are reported with the line and the column of the invalid transition in
+the file containing it, and the crate is rebuilt when any of the files changes.
+
+#### Importing SCXML
+
+Machines authored in [SCXML][scxml] tools can be turned into the same module
+as the one generated by `state_machine` with the `state_machine_from_file`
+macro. The path is relative to the directory containing `Cargo.toml` of the
+crate:
+
+```rust,ignore
+state_machine_from_file! {
+    #[derive(Debug)]
+    pub circuit_breaker = "machines/circuit_breaker.scxml"
+}
+```
+
+The name may be omitted when the document has the `name` attribute. Only flat
+machines are supported: top-level `<state>` and `<final>` elements containing
+`<transition>` elements with a single `event` and a single `target`. The
+outputs of a transition are taken from the `<send>` and `<raise>` elements
+inside it, a `<send>` element may only target `#_parent`. The documents
+exported with `#[state_machine(export(scxml))]` can be imported back.
+Other constructs (nested and parallel states, conditions, executable content)
+are reported as compile errors pointing at the line of the document.
+
+#### Completeness and default transitions
+
+By default, the combinations of a state and an input without a transition are
+rejected by `consume`. `#[state_machine(complete)]` makes every missing
+combination a compile error, listing all of them. States marked with
+`#[state(final)]` are exempt:
+
+```rust,ignore
+state_machine! {
+    #[state_machine(complete)]
+    switch(Off)
+
+    Off => {
+        Toggle => On,
+        Reset => Off
+    },
+    On => {
+        Toggle => Off [Click],
+        Reset => Off [Click]
+    }
+}
+```
+
+Alternatively, a default policy handles the missing combinations in the
+generated `transition` function, which also satisfies `complete`:
+
+- `#[state_machine(default = ignore)]` stays in the same state without an
+  output. These transitions are not drawn in the diagrams.
+- `#[state_machine(default = self)]` adds self-loops without an output. These
+  transitions are drawn in the diagrams.
+- `#[state_machine(default = error)]` rejects the input, like the machines
+  without a policy.
+
+#### Minimization
+
+Equivalent states (the states from which the machine behaves identically for
+any sequence of inputs) can be detected by the `state_machine` macro using
+Hopcroft's algorithm:
+
+- `#[state_machine(warn_mergeable)]` reports every group of equivalent states as
+  a compile warning.
+- `#[state_machine(minimize)]` additionally generates the `minimized` module
+  with the machine where all equivalent states are merged and the
+  `minimized::map_state` function that maps the original states to the merged
+  ones.
+
+```rust
+use rust_fsm::*;
+
+state_machine! {
+    #[state_machine(minimize)]
+    door(Open)
+
+    Open(Key) => Closed,
+    Closed(Key) => Open,
+    Open(Break) => Broken,
+    Closed(Break) => Broken,
+}
+
+// `Open` and `Closed` are merged into `Open`.
+let mut machine = door::minimized::StateMachine::new();
+machine.consume(&door::Input::Key).unwrap();
+```
+
+The same analysis is available at runtime for any machine implementing the
+`StateMachineReflect` trait (implemented by the macro automatically):
+
+```rust,ignore
+use rust_fsm::reflect::TransitionTable;
+
+let minimization = TransitionTable::of::<door::Impl>().minimize();
+for group in minimization.mergeable_states() {
+    // `group` is a list of indices of equivalent states...
+}
+```
+
+#### Const evaluation
+
+The macro generates `const fn` versions of the transition and output functions
+as `Impl::transition` and `Impl::output`, so the states can be computed at
+compile time, e.g. for static protocol tables in firmware. The
+`fold_transitions` macro folds a whole input sequence in a `const` context:
+
+```rust
+use rust_fsm::*;
+
+state_machine! {
+    protocol(Idle)
+
+    Idle(Connect) => Connecting,
+    Connecting(Connected) => Ready,
+    Ready(Send) => Ready [Transmit],
+}
+
+use protocol::{Impl, Input, State};
+
+// Outputs `None` if any of the transitions is impossible.
+const SESSION: Option<State> = fold_transitions!(
+    Impl::transition,
+    State::Idle,
+    &[Input::Connect, Input::Connected, Input::Send],
+);
+const _: () = assert!(matches!(SESSION, Some(State::Ready)));
+
+static MACHINE: std::sync::Mutex<protocol::StateMachine> =
+    std::sync::Mutex::new(protocol::StateMachine::new());
+```
+
+The `const` functions are generated for both backends: they match on the state
+and the input with the match backend and read the tables with the table one.
+They are not generated for machines with generic parameters or custom types,
+which only implement the transition and output functions of
+`StateMachineImpl`.
+
+#### Table backend
+
+By default the transition and output functions are a `match` over the
+combinations of a state and an input. For machines with hundreds of states such
+a `match` becomes huge and slow to compile, so
+`#[state_machine(backend = "table")]` generates dense static tables indexed by
+the state and the input instead:
+
+```rust,ignore
+state_machine! {
+    #[state_machine(backend = "table")]
+    protocol(Start)
+
+    // Hundreds of states...
+}
+```
+
+The generated enums derive `Clone` and `Copy` and are `#[repr(u16)]`, so the
+states and the inputs are cast to the indices in the tables. Custom types and
+the enums of generic machines are converted with the reflection instead. The
+observable behavior of the machine is the same as with the `match` backend.
+
+#### Typestate API
+
+With `#[state_machine(typestate)]` the macro also generates the `typestate`
+module where impossible transitions are compile errors instead of
+`TransitionImpossibleError`s. Every state is a zero-sized type and
+`typestate::Machine<S>` has a method for every transition from the state `S`,
+named after the input in `snake_case`. The method consumes the machine and
+gives it back in the new state along with the outputs of the transition, if
+any:
+
+```rust
+use rust_fsm::*;
+
+state_machine! {
+    #[derive(Debug, PartialEq)]
+    #[state_machine(typestate)]
+    door(Open)
+
+    Open(Close) => Closed,
+    Closed(Lock) => Locked [Click],
+    Locked(Unlock) => Closed,
+    Closed(Open) => Open,
+}
+
+let machine = door::typestate::Machine::new();
+let (machine, output) = machine.close().lock();
+assert_eq!(output, door::Output::Click);
+
+// The machine can continue as the enum-based one.
+let machine = door::StateMachine::from(machine);
+assert_eq!(machine.state(), &door::State::Locked);
+```
+
+```rust,compile_fail
+use rust_fsm::*;
+
+state_machine! {
+    #[state_machine(typestate)]
+    door(Open)
+
+    Open(Close) => Closed,
+    Closed(Lock) => Locked,
+}
+
+// An open door cannot be locked.
+door::typestate::Machine::new().lock();
+```
+
+The typestate API coexists with the enum-based `Impl`: the `TypeState` trait
+implemented by the state types gives the corresponding value of `State`.
+
+#### Equivalence and refinement
+
+When refactoring a machine definition, `equivalence::check_equivalence` can
+prove that the new definition behaves identically to the old one. The machines
+are compared by the names of their inputs and outputs, and the shortest input
+sequence distinguishing them is returned if they differ.
+`equivalence::check_refinement` checks that an implementation accepts at least
+everything the specification accepts, with the same outputs.
+
+```rust,ignore
+use rust_fsm::equivalence::check_equivalence;
+
+#[test]
+fn refactoring_preserves_behavior() {
+    check_equivalence::<door::Impl, door_refactored::Impl>().unwrap();
+}
+```
+
+#### Property-based testing
+
+With the `proptest` feature enabled, `testing::proptest::input_sequence` and
+`testing::proptest::accepted_input_sequence` provide strategies generating
+input sequences for any reflected machine. `testing::proptest::check_model` runs
+the machine as a model alongside your own implementation (anything implementing
+`testing::proptest::SystemUnderTest`) and shrinks a failing sequence to a minimal
+reproduction. The `quickcheck` feature provides the
+`testing::quickcheck::InputSequence` type implementing `Arbitrary`.
+
+```rust,ignore
+use rust_fsm::testing::proptest::check_model;
+
+#[test]
+fn breaker_follows_the_model() {
+    check_model::<circuit_breaker::Impl, _>(Config::default(), 32, MyBreaker::new)
+        .unwrap();
+}
+```
+
+#### Transition coverage
+
+`testing::coverage::CoverageStateMachine` is a drop-in replacement for
+`StateMachine` in tests that records every performed transition. Its coverage
+record lists the transitions that were never exercised and can fail a test if
+the coverage is below a threshold. Records of several machines can be merged.
+
+```rust,ignore
+use rust_fsm::testing::coverage::CoverageStateMachine;
+
+let mut machine = CoverageStateMachine::<circuit_breaker::Impl>::new();
+// Exercise the machine...
+machine.coverage().assert_at_least(1.0);
+```
+
+#### Test sequence generation
+
+`testing::sequences` generates input sequences for conformance testing of an
+implementation against a machine definition:
+
+- `state_cover` - the shortest input sequence leading to every state;
+- `transition_tour` - sequences exercising every transition (a single optimal
+  Chinese postman tour when the machine is strongly connected);
+- `characterization_set` - sequences distinguishing every pair of
+  non-equivalent states;
+- `w_method` - a complete test suite for implementations with a bounded number
+  of extra states.
+
+```rust,ignore
+use rust_fsm::testing::sequences::transition_tour;
+
+for sequence in transition_tour::<circuit_breaker::Impl>() {
+    // Feed `sequence` to the system under test...
+}
+```
+
 ### Without DSL
 
 The `state_machine` macro has limited capabilities (for example, a state
@@ -214,17 +712,151 @@ want to write a more complex state machine by hand.
 
 All you need to do to build a state machine is to implement the
 `StateMachineImpl` trait and use it in conjuctions with some of the provided
-wrappers (for now there is only `StateMachine`).
+wrappers: `StateMachine`, `SharedStateMachine` or `AtomicStateMachine`.
 
 You can see an example of the Circuit Breaker state machine in the
 [project repository][repo].
 
+### Sharing machines between threads
+
+`SharedStateMachine` (requires `std`) consumes the inputs through a shared
+reference, so there is no need to wrap `StateMachine` into `Arc<Mutex<_>>`. The
+state is guarded by a read-write lock, and the inputs rejected in the current
+state only take the read lock:
+
+```rust,ignore
+use rust_fsm::SharedStateMachine;
+use std::sync::Arc;
+
+let machine = Arc::new(SharedStateMachine::<circuit_breaker::Impl>::new());
+machine.consume(&circuit_breaker::Input::Unsuccessful).unwrap();
+assert_eq!(machine.state(), circuit_breaker::State::Open);
+```
+
+When the states are fieldless, `AtomicStateMachine` does not lock at all: the
+state is stored in an atomic integer and the transitions are performed with a
+compare-and-swap loop calling the transition function, also in `no_std`
+environments (using `core::sync::atomic`). The `state_machine` macro generates
+the lossless conversions of `State` to and from integers, along with the
+`AtomicStateMachine` type alias. The state is stored in an `AtomicU8`, or in an
+`AtomicU32` for machines with more than 256 states:
+
+```rust
+use rust_fsm::*;
+
+state_machine! {
+    #[derive(Debug, PartialEq)]
+    circuit_breaker(Closed)
+
+    Closed(Unsuccessful) => Open [SetupTimer],
+    Open(TimerTriggered) => HalfOpen,
+    HalfOpen => {
+        Successful => Closed,
+        Unsuccessful => Open [SetupTimer]
+    }
+}
+
+static BREAKER: std::sync::OnceLock<circuit_breaker::AtomicStateMachine> =
+    std::sync::OnceLock::new();
+
+let breaker = BREAKER.get_or_init(circuit_breaker::AtomicStateMachine::new);
+breaker.consume(&circuit_breaker::Input::Unsuccessful).unwrap();
+assert_eq!(breaker.state(), circuit_breaker::State::Open);
+```
+
+For the machines written by hand the state type must implement the
+`AtomicState` trait:
+
+```rust,ignore
+use rust_fsm::AtomicState;
+
+impl AtomicState for CircuitBreakerState {
+    type Atomic = core::sync::atomic::AtomicU8;
+
+    fn to_bits(&self) -> u8 {
+        *self as u8
+    }
+
+    fn from_bits(bits: u8) -> Option<Self> {
+        [Self::Closed, Self::Open, Self::HalfOpen]
+            .into_iter()
+            .find(|state| state.to_bits() == bits)
+    }
+}
+```
+
+### Command line tool
+
+The `rust-fsm-cli` crate provides the `rust-fsm` binary that inspects machines
+without compiling the crate defining them. It accepts `.fsm` files and Rust
+source files, in which case every invocation of the `state_machine` macro is
+inspected:
+
+```bash
+cargo install rust-fsm-cli
+# Report unreachable states, conflicting transitions and unhandled inputs.
+rust-fsm check --deny-warnings src/machines.rs machines/circuit_breaker.fsm
+# Print the diagram in the Mermaid, DOT, PlantUML or SCXML format.
+rust-fsm render --format dot --machine circuit_breaker src/machines.rs
+# Print the numbers of states, inputs, outputs and transitions.
+rust-fsm stats src/machines.rs
+```
+
+`check` exits with a non-zero code when conflicting transitions are found, or
+on any issue with `--deny-warnings`, so it can be used in CI.
+
+### Using the definitions in other tools
+
+The `rust-fsm-dsl-core` crate exposes the parser of the DSL along with a stable
+intermediate representation of machines, `Machine`, which lists the states,
+inputs, outputs, transitions and attributes of a machine as plain strings. The
+macros, the diagram generators and the command line tool are all built on it:
+
+```rust,ignore
+use rust_fsm_dsl_core::{diagram, Machine};
+
+let machine = Machine::parse_str(&std::fs::read_to_string("door.fsm")?)?;
+println!("{}", diagram::dot(&machine));
+```
+
+A `Machine` can also be parsed from tokens with `Machine::from_tokens` or built
+directly with `Machine::new` from the transitions created with
+`Transition::new`. The structs of the model are non-exhaustive: new settings
+may be added to them without a breaking change, so they are modified through
+their fields rather than created as struct literals.
+
+### Generating machines in build scripts
+
+The `rust-fsm-build` crate generates the same module as the `state_machine`
+macro from a build script, without procedural macros. The definition is taken
+either from a file written in the DSL or from a `Machine`, and the formatted
+code is written into `OUT_DIR`:
+
+```rust,ignore
+// build.rs
+fn main() {
+    rust_fsm_build::compile("machines/circuit_breaker.fsm").unwrap();
+}
+```
+
+```rust,ignore
+// src/lib.rs
+include!(concat!(env!("OUT_DIR"), "/circuit_breaker.rs"));
+```
+
+Included files are resolved relative to the including file, and the build
+script is rerun when any of the files changes. `rust_fsm_build::Builder` sets a
+different output directory and enables the diagrams in the documentation.
+
 [repo]: https://github.com/eugene-babichenko/rust-fsm
 [docs-badge]: https://docs.rs/rust-fsm/badge.svg
 [docs-link]: https://docs.rs/rust-fsm
 [crate-badge]: https://img.shields.io/crates/v/rust-fsm.svg
 [crate-link]: https://crates.io/crates/rust-fsm
 [mermaid]: https://mermaid.js.org/
+[graphviz]: https://graphviz.org/
+[plantuml]: https://plantuml.com/state-diagram
+[scxml]: https://www.w3.org/TR/scxml/
 */
 
 #![cfg_attr(not(feature = "std"), no_std)]
@@ -234,11 +866,31 @@ use core::fmt;
 use std::error::Error;
 
 #[cfg(feature = "dsl")]
-pub use rust_fsm_dsl::state_machine;
+pub use rust_fsm_dsl::{state_machine, state_machine_from_file};
 
 #[cfg(feature = "diagram")]
 pub use aquamarine::aquamarine;
 
+#[cfg(target_has_atomic = "8")]
+pub mod atomic;
+#[cfg(feature = "std")]
+pub mod diagram;
+#[cfg(feature = "std")]
+pub mod equivalence;
+#[cfg(feature = "std")]
+pub mod minimize;
+pub mod reflect;
+#[cfg(feature = "std")]
+pub mod shared;
+#[cfg(feature = "std")]
+pub mod testing;
+
+#[cfg(target_has_atomic = "8")]
+pub use atomic::{AtomicState, AtomicStateMachine};
+pub use reflect::StateMachineReflect;
+#[cfg(feature = "std")]
+pub use shared::SharedStateMachine;
+
 /// This trait is designed to describe any possible deterministic finite state
 /// machine/transducer. This is just a formal definition that may be
 /// inconvenient to be used in practical programming, but it is used throughout
@@ -262,6 +914,25 @@ pub trait StateMachineImpl {
     /// based on the current state and the given input. Outputs `None` when
     /// there is no output for a given combination of the input and the state.
     fn output(state: &Self::State, input: &Self::Input) -> Option<Self::Output>;
+    /// The output function for transitions giving several outputs: outputs
+    /// the output with the given index among the outputs given in `state` on
+    /// `input`, or `None` past the last one. The first output is the one given
+    /// by `output`. The default implementation gives at most one output.
+    fn nth_output(state: &Self::State, input: &Self::Input, index: usize) -> Option<Self::Output> {
+        if index == 0 {
+            Self::output(state, input)
+        } else {
+            None
+        }
+    }
+    /// The output of the state itself for Moore-style machines: the output
+    /// given when the machine enters `state`. The transitions entering the
+    /// state give this output after their own ones. The default implementation
+    /// outputs `None`.
+    fn state_output(state: &Self::State) -> Option<Self::Output> {
+        let _ = state;
+        None
+    }
 }
 
 /// A convenience wrapper around the `StateMachine` trait that encapsulates the
@@ -282,13 +953,13 @@ where
 {
     /// Create a new instance of this wrapper which encapsulates the initial
     /// state.
-    pub fn new() -> Self {
+    pub const fn new() -> Self {
         Self::from_state(T::INITIAL_STATE)
     }
 
     /// Create a new instance of this wrapper which encapsulates the given
     /// state.
-    pub fn from_state(state: T::State) -> Self {
+    pub const fn from_state(state: T::State) -> Self {
         Self { state }
     }
 
@@ -308,12 +979,59 @@ where
         }
     }
 
+    /// Consumes the provided input, performs a state transition and gives all
+    /// outputs of the transition. If a state transition with the current state
+    /// and the provided input is not allowed, returns an error.
+    pub fn consume_outputs<'a>(
+        &mut self,
+        input: &'a T::Input,
+    ) -> Result<Outputs<'a, T>, TransitionImpossibleError> {
+        let state = T::transition(&self.state, input).ok_or(TransitionImpossibleError)?;
+        Ok(Outputs {
+            state: core::mem::replace(&mut self.state, state),
+            input,
+            index: 0,
+        })
+    }
+
     /// Returns the current state.
     pub fn state(&self) -> &T::State {
         &self.state
     }
+
+    /// Returns the output of the current state, see
+    /// [`StateMachineImpl::state_output`].
+    pub fn current_output(&self) -> Option<T::Output> {
+        T::state_output(&self.state)
+    }
 }
 
+/// An iterator over the outputs of a transition, see
+/// [`StateMachine::consume_outputs`] and
+/// [`AtomicStateMachine::consume_outputs`]. It does not allocate: the outputs are
+/// produced by [`StateMachineImpl::nth_output`] one by one.
+pub struct Outputs<'a, T: StateMachineImpl> {
+    /// The state the transition was performed from.
+    state: T::State,
+    input: &'a T::Input,
+    index: usize,
+}
+
+impl<T> Iterator for Outputs<'_, T>
+where
+    T: StateMachineImpl,
+{
+    type Item = T::Output;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let output = T::nth_output(&self.state, self.input, self.index)?;
+        self.index += 1;
+        Some(output)
+    }
+}
+
+impl<T> core::iter::FusedIterator for Outputs<'_, T> where T: StateMachineImpl {}
+
 impl<T> Default for StateMachine<T>
 where
     T: StateMachineImpl,
@@ -338,3 +1056,36 @@ impl Error for TransitionImpossibleError {
         None
     }
 }
+
+/// Fold a sequence of inputs with a `const fn` transition function, such as
+/// the `Impl::transition` function generated by the `state_machine` macro:
+/// outputs the state reached from the given state, or `None` if one of the
+/// transitions is impossible. The expansion is `const`-evaluable, so that a
+/// sequence can be validated at compile time.
+///
+/// ```rust,ignore
+/// const STATE: Option<State> = rust_fsm::fold_transitions!(
+///     circuit_breaker::Impl::transition,
+///     State::Closed,
+///     &[Input::Unsuccessful, Input::TimerTriggered],
+/// );
+/// ```
+///
+/// Trait functions cannot be called in `const` contexts, hence a macro
+/// instead of a function generic over `StateMachineImpl`.
+#[macro_export]
+macro_rules! fold_transitions {
+    ($transition:path, $state:expr, $inputs:expr $(,)?) => {{
+        let inputs: &[_] = $inputs;
+        let mut state = ::core::option::Option::Some($state);
+        let mut index = 0;
+        while index < inputs.len() {
+            state = match state {
+                ::core::option::Option::Some(current) => $transition(&current, &inputs[index]),
+                ::core::option::Option::None => ::core::option::Option::None,
+            };
+            index += 1;
+        }
+        state
+    }};
+}
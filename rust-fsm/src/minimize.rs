@@ -0,0 +1,112 @@
+//! Minimization of state machines using Hopcroft's algorithm.
+//!
+//! Two states are equivalent when the machine gives the same outputs and
+//! rejects the same inputs for any sequence of inputs starting from either of
+//! them. Equivalent states can be merged without changing the observable
+//! behavior of the machine.
+
+use crate::reflect::TransitionTable;
+
+/// The result of the minimization of a [`TransitionTable`]. Maps every state of
+/// the original machine to a class of equivalent states.
+#[derive(Debug, Clone)]
+pub struct Minimization {
+    original: TransitionTable,
+    classes: Vec<usize>,
+    num_classes: usize,
+}
+
+impl TransitionTable {
+    /// Compute the classes of equivalent states of this machine.
+    pub fn minimize(&self) -> Minimization {
+        let classes = rust_fsm_minimize::equivalence_classes(
+            self.num_states(),
+            self.num_inputs(),
+            |state, input| self.transition(state, input),
+            |state, input| self.outputs(state, input),
+            |state| self.state_output(state),
+        );
+        let num_classes = classes.iter().max().map_or(0, |max| max + 1);
+
+        Minimization {
+            original: self.clone(),
+            classes,
+            num_classes,
+        }
+    }
+}
+
+impl Minimization {
+    /// The index of the class the given state belongs to. Classes are numbered
+    /// in the order of the first appearance of their members in the original
+    /// list of states.
+    pub fn class_of(&self, state: usize) -> usize {
+        self.classes[state]
+    }
+
+    /// The number of classes of equivalent states, i.e. the number of states
+    /// in the minimized machine.
+    pub fn num_classes(&self) -> usize {
+        self.num_classes
+    }
+
+    /// Outputs `true` if no states can be merged.
+    pub fn is_minimal(&self) -> bool {
+        self.num_classes == self.classes.len()
+    }
+
+    /// Groups of states (two or more in each) that are equivalent and can be
+    /// merged.
+    pub fn mergeable_states(&self) -> Vec<Vec<usize>> {
+        let mut groups = vec![Vec::new(); self.num_classes];
+        for (state, &class) in self.classes.iter().enumerate() {
+            groups[class].push(state);
+        }
+        groups.retain(|group| group.len() > 1);
+        groups
+    }
+
+    /// Build the minimized machine. Each class of equivalent states is named
+    /// after its representative: the initial state if it belongs to the class
+    /// or the first member of the class otherwise.
+    pub fn minimized(&self) -> TransitionTable {
+        let initial_class = self.classes[self.original.initial_state()];
+        let mut representatives = vec![None; self.num_classes];
+        representatives[initial_class] = Some(self.original.initial_state());
+        for (state, &class) in self.classes.iter().enumerate() {
+            representatives[class].get_or_insert(state);
+        }
+        let representatives: Vec<usize> = representatives.into_iter().flatten().collect();
+
+        let mut transitions = Vec::new();
+        let mut outputs = Vec::new();
+        for &state in &representatives {
+            for input in 0..self.original.num_inputs() {
+                transitions.push(
+                    self.original
+                        .transition(state, input)
+                        .map(|next| self.classes[next]),
+                );
+                outputs.push(self.original.outputs(state, input).to_vec());
+            }
+        }
+
+        TransitionTable::from_parts(
+            representatives
+                .iter()
+                .map(|&state| self.original.state_names()[state])
+                .collect(),
+            self.original.input_names().to_vec(),
+            self.original.output_names().to_vec(),
+            initial_class,
+            transitions,
+            outputs,
+        )
+        .with_state_outputs(
+            representatives
+                .iter()
+                .map(|&state| self.original.state_output(state))
+                .collect(),
+        )
+    }
+}
@@ -0,0 +1,233 @@
+//! Reflection of state machines with finite, enumerable alphabets.
+//!
+//! The [`StateMachineReflect`] trait exposes the states, inputs and outputs of
+//! a machine as indexed lists. It is implemented automatically for every
+//! machine generated by the `state_machine` macro. On top of it the
+//! [`TransitionTable`] (available with the `std` feature) captures the whole
+//! transition and output functions of a machine, which allows to analyze it at
+//! runtime.
+
+use crate::StateMachineImpl;
+
+/// A state machine whose states, inputs and outputs can be enumerated. Each of
+/// these entities is identified by an index into the corresponding list of
+/// names.
+pub trait StateMachineReflect: StateMachineImpl {
+    /// The names of all states, in the order of their indices.
+    const STATE_NAMES: &'static [&'static str];
+    /// The names of all inputs, in the order of their indices.
+    const INPUT_NAMES: &'static [&'static str];
+    /// The names of all outputs, in the order of their indices.
+    const OUTPUT_NAMES: &'static [&'static str];
+
+    /// Returns the state with the given index, if any.
+    fn state_at(index: usize) -> Option<Self::State>;
+    /// Returns the input with the given index, if any.
+    fn input_at(index: usize) -> Option<Self::Input>;
+    /// Returns the output with the given index, if any.
+    fn output_at(index: usize) -> Option<Self::Output>;
+    /// Returns the index of the given state. Outputs `None` when the state is
+    /// not known to the reflection (e.g. a variant of a custom state type that
+    /// is not used in the machine definition).
+    fn state_index(state: &Self::State) -> Option<usize>;
+    /// Returns the index of the given input. Outputs `None` when the input is
+    /// not known to the reflection.
+    fn input_index(input: &Self::Input) -> Option<usize>;
+    /// Returns the index of the given output. Outputs `None` when the output
+    /// is not known to the reflection.
+    fn output_index(output: &Self::Output) -> Option<usize>;
+}
+
+#[cfg(feature = "std")]
+pub use self::table::TransitionTable;
+
+#[cfg(feature = "std")]
+mod table {
+    use super::StateMachineReflect;
+
+    /// The transition and output functions of a state machine evaluated for
+    /// every combination of a state and an input. States, inputs and outputs
+    /// are referred to by their indices.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct TransitionTable {
+        state_names: Vec<&'static str>,
+        input_names: Vec<&'static str>,
+        output_names: Vec<&'static str>,
+        initial_state: usize,
+        transitions: Vec<Option<usize>>,
+        outputs: Vec<Vec<usize>>,
+        state_outputs: Vec<Option<usize>>,
+    }
+
+    impl TransitionTable {
+        /// Build the transition table of the given state machine.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the initial state of the machine is not known to its
+        /// reflection.
+        pub fn of<T: StateMachineReflect>() -> Self {
+            let initial_state = T::state_index(&T::INITIAL_STATE)
+                .expect("the initial state must be known to the reflection");
+            let mut transitions = Vec::new();
+            let mut outputs = Vec::new();
+            for state in (0..T::STATE_NAMES.len()).filter_map(T::state_at) {
+                for input in (0..T::INPUT_NAMES.len()).filter_map(T::input_at) {
+                    let transition =
+                        T::transition(&state, &input).and_then(|state| T::state_index(&state));
+                    let transition_outputs = match transition {
+                        Some(_) => (0..)
+                            .map_while(|index| T::nth_output(&state, &input, index))
+                            .filter_map(|output| T::output_index(&output))
+                            .collect(),
+                        None => Vec::new(),
+                    };
+                    transitions.push(transition);
+                    outputs.push(transition_outputs);
+                }
+            }
+            let state_outputs = (0..T::STATE_NAMES.len())
+                .map(|index| {
+                    let state = T::state_at(index)?;
+                    T::output_index(&T::state_output(&state)?)
+                })
+                .collect();
+            Self {
+                state_names: T::STATE_NAMES.to_vec(),
+                input_names: T::INPUT_NAMES.to_vec(),
+                output_names: T::OUTPUT_NAMES.to_vec(),
+                initial_state,
+                transitions,
+                outputs,
+                state_outputs,
+            }
+        }
+
+        /// Build a transition table from raw parts. `transitions` and `outputs`
+        /// (the lists of outputs of the transitions) are indexed by
+        /// `state * input_names.len() + input`. Outputs for combinations
+        /// without a transition are discarded. The states have no outputs of
+        /// their own, see [`with_state_outputs`](Self::with_state_outputs).
+        ///
+        /// # Panics
+        ///
+        /// Panics if the sizes of the tables do not match the number of states
+        /// and inputs or if any index is out of bounds.
+        pub fn from_parts(
+            state_names: Vec<&'static str>,
+            input_names: Vec<&'static str>,
+            output_names: Vec<&'static str>,
+            initial_state: usize,
+            transitions: Vec<Option<usize>>,
+            outputs: Vec<Vec<usize>>,
+        ) -> Self {
+            let size = state_names.len() * input_names.len();
+            assert_eq!(transitions.len(), size, "invalid transitions table size");
+            assert_eq!(outputs.len(), size, "invalid outputs table size");
+            assert!(initial_state < state_names.len(), "invalid initial state");
+            assert!(
+                transitions.iter().flatten().all(|&s| s < state_names.len()),
+                "invalid state index in the transitions table"
+            );
+            assert!(
+                outputs.iter().flatten().all(|&o| o < output_names.len()),
+                "invalid output index in the outputs table"
+            );
+            let outputs = outputs
+                .into_iter()
+                .zip(&transitions)
+                .map(|(outputs, transition)| match transition {
+                    Some(_) => outputs,
+                    None => Vec::new(),
+                })
+                .collect();
+            let state_outputs = vec![None; state_names.len()];
+            Self {
+                state_names,
+                input_names,
+                output_names,
+                initial_state,
+                transitions,
+                outputs,
+                state_outputs,
+            }
+        }
+
+        /// Set the outputs of the states themselves, indexed by state.
+        ///
+        /// # Panics
+        ///
+        /// Panics if the size of the table does not match the number of states
+        /// or if any index is out of bounds.
+        pub fn with_state_outputs(mut self, state_outputs: Vec<Option<usize>>) -> Self {
+            assert_eq!(
+                state_outputs.len(),
+                self.num_states(),
+                "invalid state outputs table size"
+            );
+            assert!(
+                state_outputs
+                    .iter()
+                    .flatten()
+                    .all(|&o| o < self.output_names.len()),
+                "invalid output index in the state outputs table"
+            );
+            self.state_outputs = state_outputs;
+            self
+        }
+
+        /// The names of the states.
+        pub fn state_names(&self) -> &[&'static str] {
+            &self.state_names
+        }
+
+        /// The names of the inputs.
+        pub fn input_names(&self) -> &[&'static str] {
+            &self.input_names
+        }
+
+        /// The names of the outputs.
+        pub fn output_names(&self) -> &[&'static str] {
+            &self.output_names
+        }
+
+        /// The number of states.
+        pub fn num_states(&self) -> usize {
+            self.state_names.len()
+        }
+
+        /// The number of inputs.
+        pub fn num_inputs(&self) -> usize {
+            self.input_names.len()
+        }
+
+        /// The index of the initial state.
+        pub fn initial_state(&self) -> usize {
+            self.initial_state
+        }
+
+        /// The index of the state the machine moves to from `state` on `input`.
+        /// Outputs `None` when there is no such transition.
+        pub fn transition(&self, state: usize, input: usize) -> Option<usize> {
+            self.transitions[state * self.num_inputs() + input]
+        }
+
+        /// The index of the first output the machine gives in `state` on
+        /// `input`. Outputs `None` when there is no output or no transition.
+        pub fn output(&self, state: usize, input: usize) -> Option<usize> {
+            self.outputs(state, input).first().copied()
+        }
+
+        /// The indices of all outputs the machine gives in `state` on `input`,
+        /// in order. Empty when there is no output or no transition.
+        pub fn outputs(&self, state: usize, input: usize) -> &[usize] {
+            &self.outputs[state * self.num_inputs() + input]
+        }
+
+        /// The index of the output of `state` itself, given when the machine
+        /// enters it. Outputs `None` when the state has no output.
+        pub fn state_output(&self, state: usize) -> Option<usize> {
+            self.state_outputs[state]
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! A state machine shared between threads.
+//!
+//! [`SharedStateMachine`] guards the state with a read-write lock, so that the
+//! inputs can be consumed through a shared reference, e.g. from an `Arc`. For
+//! machines with fieldless states see also
+//! [`AtomicStateMachine`](crate::atomic::AtomicStateMachine), which does not
+//! lock at all.
+
+use crate::{StateMachine, StateMachineImpl, TransitionImpossibleError};
+use std::{
+    fmt,
+    sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard},
+};
+
+/// A wrapper around the state of a machine that consumes inputs via `&self`.
+///
+/// Inputs rejected in the current state only take the read lock, so that the
+/// common case of a circuit breaker (requests succeeding in the `Closed` state
+/// without a transition) does not serialize the callers.
+pub struct SharedStateMachine<T: StateMachineImpl> {
+    state: RwLock<T::State>,
+}
+
+impl<T> SharedStateMachine<T>
+where
+    T: StateMachineImpl,
+{
+    /// Create a new instance of this wrapper which encapsulates the initial
+    /// state.
+    pub fn new() -> Self {
+        Self::from_state(T::INITIAL_STATE)
+    }
+
+    /// Create a new instance of this wrapper which encapsulates the given
+    /// state.
+    pub fn from_state(state: T::State) -> Self {
+        Self {
+            state: RwLock::new(state),
+        }
+    }
+
+    /// Consumes the provided input, gives an output and performs a state
+    /// transition. If a state transition with the current state and the
+    /// provided input is not allowed, returns an error.
+    pub fn consume(
+        &self,
+        input: &T::Input,
+    ) -> Result<Option<T::Output>, TransitionImpossibleError> {
+        if T::transition(&self.read(), input).is_none() {
+            return Err(TransitionImpossibleError);
+        }
+        // The state may have changed between releasing the read lock and
+        // taking the write lock, so the transition is evaluated again.
+        let mut state = self.write();
+        let next = T::transition(&state, input).ok_or(TransitionImpossibleError)?;
+        let output = T::output(&state, input);
+        *state = next;
+        Ok(output)
+    }
+
+    /// Consumes the provided input, performs a state transition and gives all
+    /// outputs of the transition. If a state transition with the current state
+    /// and the provided input is not allowed, returns an error.
+    pub fn consume_outputs(
+        &self,
+        input: &T::Input,
+    ) -> Result<Vec<T::Output>, TransitionImpossibleError> {
+        let mut state = self.write();
+        let next = T::transition(&state, input).ok_or(TransitionImpossibleError)?;
+        let outputs = (0..)
+            .map_while(|index| T::nth_output(&state, input, index))
+            .collect();
+        *state = next;
+        Ok(outputs)
+    }
+
+    /// Returns a copy of the current state.
+    pub fn state(&self) -> T::State
+    where
+        T::State: Clone,
+    {
+        self.read().clone()
+    }
+
+    /// Returns the output of the current state, see
+    /// [`StateMachineImpl::state_output`].
+    pub fn current_output(&self) -> Option<T::Output> {
+        T::state_output(&self.read())
+    }
+
+    /// Unwraps the machine.
+    pub fn into_inner(self) -> StateMachine<T> {
+        let state = self
+            .state
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner);
+        StateMachine::from_state(state)
+    }
+
+    // The state is only replaced after the transition and output functions
+    // return, so a panic in them cannot leave it inconsistent and the poisoning
+    // is ignored.
+    fn read(&self) -> RwLockReadGuard<'_, T::State> {
+        self.state.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, T::State> {
+        self.state.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T> Default for SharedStateMachine<T>
+where
+    T: StateMachineImpl,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> From<StateMachine<T>> for SharedStateMachine<T>
+where
+    T: StateMachineImpl,
+{
+    fn from(machine: StateMachine<T>) -> Self {
+        Self::from_state(machine.state)
+    }
+}
+
+impl<T> fmt::Debug for SharedStateMachine<T>
+where
+    T: StateMachineImpl,
+    T::State: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SharedStateMachine")
+            .field("state", &*self.read())
+            .finish()
+    }
+}
@@ -0,0 +1,8 @@
+//! Tools for testing state machines and systems that implement them.
+
+pub mod coverage;
+#[cfg(feature = "proptest")]
+pub mod proptest;
+#[cfg(feature = "quickcheck")]
+pub mod quickcheck;
+pub mod sequences;
@@ -0,0 +1,177 @@
+//! Tracking which transitions of a machine were exercised.
+
+use crate::{
+    reflect::{StateMachineReflect, TransitionTable},
+    StateMachine, TransitionImpossibleError,
+};
+use std::fmt;
+
+/// A wrapper around [`StateMachine`] that records every transition it
+/// performs.
+pub struct CoverageStateMachine<T: StateMachineReflect> {
+    machine: StateMachine<T>,
+    coverage: Coverage,
+}
+
+/// The set of exercised transitions of a machine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Coverage {
+    table: TransitionTable,
+    hits: Vec<bool>,
+}
+
+impl<T> CoverageStateMachine<T>
+where
+    T: StateMachineReflect,
+{
+    /// Create a new instance of this wrapper which encapsulates the initial
+    /// state.
+    pub fn new() -> Self {
+        Self::from_state(T::INITIAL_STATE)
+    }
+
+    /// Create a new instance of this wrapper which encapsulates the given
+    /// state.
+    pub fn from_state(state: T::State) -> Self {
+        Self {
+            machine: StateMachine::from_state(state),
+            coverage: Coverage::of::<T>(),
+        }
+    }
+
+    /// Consumes the provided input, gives an output and performs a state
+    /// transition, recording it. If a state transition with the current state
+    /// and the provided input is not allowed, returns an error.
+    pub fn consume(
+        &mut self,
+        input: &T::Input,
+    ) -> Result<Option<T::Output>, TransitionImpossibleError> {
+        let state = T::state_index(self.machine.state());
+        let output = self.machine.consume(input)?;
+        if let (Some(state), Some(input)) = (state, T::input_index(input)) {
+            self.coverage.record(state, input);
+        }
+        Ok(output)
+    }
+
+    /// Returns the current state.
+    pub fn state(&self) -> &T::State {
+        self.machine.state()
+    }
+
+    /// Returns the transitions recorded so far.
+    pub fn coverage(&self) -> &Coverage {
+        &self.coverage
+    }
+}
+
+impl<T> Default for CoverageStateMachine<T>
+where
+    T: StateMachineReflect,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Coverage {
+    /// Create an empty coverage record for the given machine.
+    pub fn of<T: StateMachineReflect>() -> Self {
+        Self::from_table(TransitionTable::of::<T>())
+    }
+
+    /// Create an empty coverage record for the machine described by the given
+    /// transition table.
+    pub fn from_table(table: TransitionTable) -> Self {
+        let hits = vec![false; table.num_states() * table.num_inputs()];
+        Self { table, hits }
+    }
+
+    /// Record the transition from `state` on `input`, both given by their
+    /// indices. Combinations without a transition are ignored.
+    pub fn record(&mut self, state: usize, input: usize) {
+        if self.table.transition(state, input).is_some() {
+            self.hits[state * self.table.num_inputs() + input] = true;
+        }
+    }
+
+    /// Add the transitions recorded in `other` to this record.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the records belong to different machines.
+    pub fn merge(&mut self, other: &Coverage) {
+        assert_eq!(
+            self.table, other.table,
+            "cannot merge coverage of different machines"
+        );
+        for (hit, other) in self.hits.iter_mut().zip(&other.hits) {
+            *hit |= other;
+        }
+    }
+
+    /// The number of transitions defined in the machine.
+    pub fn total(&self) -> usize {
+        self.transitions().count()
+    }
+
+    /// The number of transitions exercised at least once.
+    pub fn covered(&self) -> usize {
+        self.transitions().filter(|&(_, _, hit)| hit).count()
+    }
+
+    /// The share of exercised transitions, from `0.0` to `1.0`. A machine
+    /// without transitions is considered fully covered.
+    pub fn ratio(&self) -> f64 {
+        match self.total() {
+            0 => 1.0,
+            total => self.covered() as f64 / total as f64,
+        }
+    }
+
+    /// The names of the state and the input of every transition that was not
+    /// exercised.
+    pub fn uncovered(&self) -> Vec<(&'static str, &'static str)> {
+        self.transitions()
+            .filter(|&(_, _, hit)| !hit)
+            .map(|(state, input, _)| {
+                (
+                    self.table.state_names()[state],
+                    self.table.input_names()[input],
+                )
+            })
+            .collect()
+    }
+
+    /// Fail (panic) if the share of exercised transitions is below
+    /// `threshold` (from `0.0` to `1.0`), listing the transitions that were not
+    /// exercised.
+    #[track_caller]
+    pub fn assert_at_least(&self, threshold: f64) {
+        if self.ratio() < threshold {
+            panic!("transition coverage is below the threshold of {threshold}: {self}");
+        }
+    }
+
+    fn transitions(&self) -> impl Iterator<Item = (usize, usize, bool)> + '_ {
+        let num_inputs = self.table.num_inputs();
+        (0..self.table.num_states())
+            .flat_map(move |state| (0..num_inputs).map(move |input| (state, input)))
+            .filter(|&(state, input)| self.table.transition(state, input).is_some())
+            .map(move |(state, input)| (state, input, self.hits[state * num_inputs + input]))
+    }
+}
+
+impl fmt::Display for Coverage {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{} transitions covered", self.covered(), self.total())?;
+        let uncovered = self.uncovered();
+        if !uncovered.is_empty() {
+            write!(f, ", uncovered:")?;
+            for (state, input) in uncovered {
+                write!(f, " {state}({input})")?;
+            }
+        }
+        Ok(())
+    }
+}
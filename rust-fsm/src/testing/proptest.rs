@@ -0,0 +1,123 @@
+//! [`proptest`](https://docs.rs/proptest) strategies and a model-based testing
+//! harness for machines with enumerable input alphabets.
+//!
+//! Strategies generate sequences of input indices and map them into the inputs
+//! of a machine, so failing sequences are shrunk by `proptest` both in length
+//! and in the choice of inputs.
+
+use crate::{
+    reflect::{StateMachineReflect, TransitionTable},
+    StateMachine, StateMachineImpl, TransitionImpossibleError,
+};
+use ::proptest::{
+    collection::{vec, SizeRange},
+    prop_assert_eq,
+    strategy::Strategy,
+    test_runner::{Config, TestCaseError, TestError, TestRunner},
+};
+use core::fmt::Debug;
+
+/// A strategy generating arbitrary sequences of inputs of the machine `T`.
+///
+/// # Panics
+///
+/// Panics if the input alphabet of `T` is empty.
+pub fn input_sequence<T>(length: impl Into<SizeRange>) -> impl Strategy<Value = Vec<T::Input>>
+where
+    T: StateMachineReflect,
+    T::Input: Debug,
+{
+    vec(0..T::INPUT_NAMES.len(), length)
+        .prop_map(|indices| indices.into_iter().filter_map(T::input_at).collect())
+}
+
+/// A strategy generating sequences of inputs of the machine `T` that are
+/// accepted by the machine when starting from its initial state. The sequence
+/// may be shorter than requested if the machine reaches a state where no input
+/// is accepted.
+pub fn accepted_input_sequence<T>(
+    length: impl Into<SizeRange>,
+) -> impl Strategy<Value = Vec<T::Input>>
+where
+    T: StateMachineReflect,
+    T::Input: Debug,
+{
+    let table = TransitionTable::of::<T>();
+    // Each generated number selects one of the inputs accepted in the current
+    // state.
+    vec(0..usize::MAX, length).prop_map(move |choices| {
+        let mut state = table.initial_state();
+        let mut inputs = Vec::new();
+        for choice in choices {
+            let accepted: Vec<_> = (0..table.num_inputs())
+                .filter(|&input| table.transition(state, input).is_some())
+                .collect();
+            if accepted.is_empty() {
+                break;
+            }
+            let input = accepted[choice % accepted.len()];
+            state = table.transition(state, input).unwrap();
+            inputs.extend(T::input_at(input));
+        }
+        inputs
+    })
+}
+
+/// A system that is expected to behave like the state machine `T`.
+pub trait SystemUnderTest<T: StateMachineImpl> {
+    /// Feed the input to the system and return its reaction.
+    fn consume(&mut self, input: &T::Input)
+        -> Result<Option<T::Output>, TransitionImpossibleError>;
+
+    /// Check that the system is in a state corresponding to the given state of
+    /// the model. By default no checks are performed.
+    fn check_state(&self, _model_state: &T::State) -> bool {
+        true
+    }
+}
+
+impl<T, U> SystemUnderTest<T> for StateMachine<U>
+where
+    T: StateMachineImpl,
+    U: StateMachineImpl<Input = T::Input, Output = T::Output>,
+{
+    fn consume(
+        &mut self,
+        input: &T::Input,
+    ) -> Result<Option<T::Output>, TransitionImpossibleError> {
+        StateMachine::consume(self, input)
+    }
+}
+
+/// Run the model machine `T` alongside systems created by `new_system` on
+/// random input sequences of up to `max_length` inputs. Each step the reaction
+/// of the system must match the reaction of the model. A failing sequence is
+/// shrunk to a minimal reproduction, which is reported in the error.
+pub fn check_model<T, S>(
+    config: Config,
+    max_length: usize,
+    new_system: impl Fn() -> S,
+) -> Result<(), TestError<Vec<T::Input>>>
+where
+    T: StateMachineReflect,
+    T::Input: Debug,
+    T::Output: Debug + PartialEq,
+    S: SystemUnderTest<T>,
+{
+    let mut runner = TestRunner::new(config);
+    runner.run(&input_sequence::<T>(0..=max_length), |inputs| {
+        let mut model = StateMachine::<T>::new();
+        let mut system = new_system();
+        for input in &inputs {
+            let expected = model.consume(input).ok();
+            let actual = system.consume(input).ok();
+            prop_assert_eq!(expected, actual, "reaction to {:?}", input);
+            if !system.check_state(model.state()) {
+                return Err(TestCaseError::fail(format!(
+                    "state mismatch after {input:?}"
+                )));
+            }
+        }
+        Ok(())
+    })
+}
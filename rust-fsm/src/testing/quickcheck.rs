@@ -0,0 +1,66 @@
+//! [`quickcheck`](https://docs.rs/quickcheck) support for machines with
+//! enumerable input alphabets.
+
+use crate::reflect::StateMachineReflect;
+use ::quickcheck::{Arbitrary, Gen};
+use core::{fmt, marker::PhantomData};
+
+/// An arbitrary sequence of inputs of the machine `T`. Shrinks both in length
+/// and in the choice of inputs.
+pub struct InputSequence<T> {
+    indices: Vec<usize>,
+    machine: PhantomData<fn() -> T>,
+}
+
+impl<T: StateMachineReflect> InputSequence<T> {
+    /// The inputs of this sequence.
+    pub fn inputs(&self) -> Vec<T::Input> {
+        self.indices
+            .iter()
+            .copied()
+            .filter_map(T::input_at)
+            .collect()
+    }
+}
+
+impl<T> Clone for InputSequence<T> {
+    fn clone(&self) -> Self {
+        Self {
+            indices: self.indices.clone(),
+            machine: PhantomData,
+        }
+    }
+}
+
+impl<T: StateMachineReflect> fmt::Debug for InputSequence<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list()
+            .entries(self.indices.iter().map(|&index| T::INPUT_NAMES[index]))
+            .finish()
+    }
+}
+
+impl<T: StateMachineReflect + 'static> Arbitrary for InputSequence<T> {
+    fn arbitrary(g: &mut Gen) -> Self {
+        let num_inputs = T::INPUT_NAMES.len();
+        let length = if num_inputs == 0 {
+            0
+        } else {
+            usize::arbitrary(g) % (g.size() + 1)
+        };
+        Self {
+            indices: (0..length)
+                .map(|_| usize::arbitrary(g) % num_inputs)
+                .collect(),
+            machine: PhantomData,
+        }
+    }
+
+    fn shrink(&self) -> Box<dyn Iterator<Item = Self>> {
+        // Shrinking an index moves it towards zero, so it always stays valid.
+        Box::new(self.indices.shrink().map(|indices| Self {
+            indices,
+            machine: PhantomData,
+        }))
+    }
+}
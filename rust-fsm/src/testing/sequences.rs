@@ -0,0 +1,471 @@
+//! Generation of input sequences for conformance testing.
+//!
+//! All sequences start from the initial state of the machine. Rejected inputs
+//! are observable: a conforming implementation must reject them too, staying
+//! in the same state.
+
+use crate::reflect::{StateMachineReflect, TransitionTable};
+use std::collections::{BTreeSet, VecDeque};
+
+/// The shortest input sequence leading from the initial state to every state.
+/// Unreachable states have no sequence.
+pub fn state_cover<T: StateMachineReflect>() -> Vec<Option<Vec<T::Input>>> {
+    TransitionTable::of::<T>()
+        .state_cover()
+        .into_iter()
+        .map(|path| path.map(inputs_of::<T>))
+        .collect()
+}
+
+/// A transition tour: input sequences that together exercise every transition
+/// reachable from the initial state. If every reachable state can be reached
+/// back from any other, this is a single sequence of the minimal length (a
+/// solution of the Chinese postman problem). Otherwise, the machine has to be
+/// reset to its initial state between the sequences.
+pub fn transition_tour<T: StateMachineReflect>() -> Vec<Vec<T::Input>> {
+    TransitionTable::of::<T>()
+        .transition_tour()
+        .into_iter()
+        .map(inputs_of::<T>)
+        .collect()
+}
+
+/// A characterization set: input sequences such that for every pair of
+/// non-equivalent states, at least one sequence gives different reactions when
+/// applied from these states.
+pub fn characterization_set<T: StateMachineReflect>() -> Vec<Vec<T::Input>> {
+    TransitionTable::of::<T>()
+        .characterization_set()
+        .into_iter()
+        .map(inputs_of::<T>)
+        .collect()
+}
+
+/// A test suite generated by the W-method. It detects any faulty
+/// implementation with at most `extra_states` states more than the
+/// specification.
+pub fn w_method<T: StateMachineReflect>(extra_states: usize) -> Vec<Vec<T::Input>> {
+    TransitionTable::of::<T>()
+        .w_method(extra_states)
+        .into_iter()
+        .map(inputs_of::<T>)
+        .collect()
+}
+
+fn inputs_of<T: StateMachineReflect>(indices: Vec<usize>) -> Vec<T::Input> {
+    indices.into_iter().filter_map(T::input_at).collect()
+}
+
+impl TransitionTable {
+    /// The shortest sequence of input indices leading from the initial state to
+    /// every state. Unreachable states have no sequence.
+    pub fn state_cover(&self) -> Vec<Option<Vec<usize>>> {
+        let parents = self.shortest_path_tree(self.initial_state());
+        (0..self.num_states())
+            .map(|state| path_from_tree(&parents, state))
+            .collect()
+    }
+
+    /// Input index sequences exercising every transition reachable from the
+    /// initial state. See [`transition_tour`].
+    pub fn transition_tour(&self) -> Vec<Vec<usize>> {
+        let reachable: Vec<bool> = self.state_cover().iter().map(Option::is_some).collect();
+        let edges: Vec<(usize, usize, usize)> = (0..self.num_states())
+            .filter(|&state| reachable[state])
+            .flat_map(|state| {
+                (0..self.num_inputs())
+                    .filter_map(move |input| Some((state, input, self.transition(state, input)?)))
+            })
+            .collect();
+        if edges.is_empty() {
+            return Vec::new();
+        }
+
+        let strongly_connected = (0..self.num_states())
+            .filter(|&state| reachable[state])
+            .all(|state| self.shortest_path_tree(state)[self.initial_state()].is_some());
+        if strongly_connected {
+            vec![self.postman_tour(edges)]
+        } else {
+            self.greedy_tours(edges)
+        }
+    }
+
+    /// Input index sequences distinguishing every pair of non-equivalent
+    /// states. See [`characterization_set`].
+    pub fn characterization_set(&self) -> Vec<Vec<usize>> {
+        let mut set: Vec<Vec<usize>> = Vec::new();
+        for first in 0..self.num_states() {
+            for second in first + 1..self.num_states() {
+                let distinguished = set.iter().any(|sequence| {
+                    self.reactions(first, sequence) != self.reactions(second, sequence)
+                });
+                if distinguished {
+                    continue;
+                }
+                if let Some(sequence) = self.distinguishing_sequence(first, second) {
+                    set.push(sequence);
+                }
+            }
+        }
+        // Drop sequences that are prefixes of other sequences: the reactions to
+        // a prefix are observed when applying the longer sequence.
+        remove_prefixes(set)
+    }
+
+    /// A W-method test suite as input index sequences. See [`w_method`].
+    pub fn w_method(&self, extra_states: usize) -> Vec<Vec<usize>> {
+        let state_cover: Vec<Vec<usize>> = self.state_cover().into_iter().flatten().collect();
+
+        // The transition cover: every reachable state followed by every input.
+        let mut transition_cover = state_cover.clone();
+        for path in &state_cover {
+            for input in 0..self.num_inputs() {
+                let mut path = path.clone();
+                path.push(input);
+                transition_cover.push(path);
+            }
+        }
+
+        // All input sequences of length up to `extra_states`.
+        let mut middles = vec![Vec::new()];
+        let mut layer = vec![Vec::new()];
+        for _ in 0..extra_states {
+            layer = layer
+                .iter()
+                .flat_map(|prefix: &Vec<usize>| {
+                    (0..self.num_inputs()).map(move |input| {
+                        let mut sequence = prefix.clone();
+                        sequence.push(input);
+                        sequence
+                    })
+                })
+                .collect();
+            middles.extend(layer.iter().cloned());
+        }
+
+        let mut characterization = self.characterization_set();
+        if characterization.is_empty() {
+            characterization.push(Vec::new());
+        }
+
+        let mut suite = BTreeSet::new();
+        for prefix in &transition_cover {
+            for middle in &middles {
+                for suffix in &characterization {
+                    suite.insert([&prefix[..], middle, suffix].concat());
+                }
+            }
+        }
+        remove_prefixes(suite.into_iter().collect())
+    }
+
+    /// Breadth-first search from `start`. For every reached state outputs the
+    /// previous state and the input leading to it (`None` for `start`).
+    fn shortest_path_tree(&self, start: usize) -> Vec<Option<Option<(usize, usize)>>> {
+        let mut parents = vec![None; self.num_states()];
+        parents[start] = Some(None);
+        let mut queue = VecDeque::from([start]);
+        while let Some(state) = queue.pop_front() {
+            for input in 0..self.num_inputs() {
+                if let Some(next) = self.transition(state, input) {
+                    if parents[next].is_none() {
+                        parents[next] = Some(Some((state, input)));
+                        queue.push_back(next);
+                    }
+                }
+            }
+        }
+        parents
+    }
+
+    /// The reactions of the machine to the sequence from `state`: the outputs
+    /// for every accepted input, stopping at the first rejected input.
+    fn reactions(&self, mut state: usize, sequence: &[usize]) -> Vec<Option<&[usize]>> {
+        let mut reactions = Vec::new();
+        for &input in sequence {
+            match self.transition(state, input) {
+                Some(next) => {
+                    reactions.push(Some(self.outputs(state, input)));
+                    state = next;
+                }
+                None => {
+                    reactions.push(None);
+                    break;
+                }
+            }
+        }
+        reactions
+    }
+
+    /// The shortest sequence giving different reactions from the two states, if
+    /// the states are not equivalent.
+    fn distinguishing_sequence(&self, first: usize, second: usize) -> Option<Vec<usize>> {
+        let num_states = self.num_states();
+        let mut parents = vec![None; num_states * num_states];
+        parents[first * num_states + second] = Some(None);
+        let mut queue = VecDeque::from([(first, second)]);
+        while let Some((a, b)) = queue.pop_front() {
+            for input in 0..self.num_inputs() {
+                let (next_a, next_b) = (self.transition(a, input), self.transition(b, input));
+                let differs = next_a.is_some() != next_b.is_some()
+                    || self.outputs(a, input) != self.outputs(b, input);
+                if differs {
+                    let mut sequence = vec![input];
+                    let mut current = (a, b);
+                    while let Some(Some((previous, input))) =
+                        parents[current.0 * num_states + current.1]
+                    {
+                        sequence.push(input);
+                        current = previous;
+                    }
+                    sequence.reverse();
+                    return Some(sequence);
+                }
+                if let (Some(next_a), Some(next_b)) = (next_a, next_b) {
+                    let slot = &mut parents[next_a * num_states + next_b];
+                    if slot.is_none() {
+                        *slot = Some(Some(((a, b), input)));
+                        queue.push_back((next_a, next_b));
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Solve the Chinese postman problem for a strongly connected machine:
+    /// duplicate the transitions along the cheapest paths balancing the number
+    /// of incoming and outgoing transitions of every state and walk the
+    /// resulting Eulerian circuit.
+    fn postman_tour(&self, mut edges: Vec<(usize, usize, usize)>) -> Vec<usize> {
+        let num_states = self.num_states();
+        let mut balance = vec![0isize; num_states];
+        for &(from, _, to) in &edges {
+            balance[from] -= 1;
+            balance[to] += 1;
+        }
+        // States with more incoming transitions need extra outgoing ones and
+        // vice versa.
+        let sources: Vec<usize> = (0..num_states).filter(|&s| balance[s] > 0).collect();
+        let sinks: Vec<usize> = (0..num_states).filter(|&s| balance[s] < 0).collect();
+        let trees: Vec<_> = sources
+            .iter()
+            .map(|&source| self.shortest_path_tree(source))
+            .collect();
+        let distance = |source: usize, sink: usize| {
+            path_from_tree(&trees[source], sinks[sink])
+                .expect("the machine is strongly connected")
+                .len()
+        };
+
+        let supply: Vec<usize> = sources.iter().map(|&s| balance[s] as usize).collect();
+        let demand: Vec<usize> = sinks.iter().map(|&s| (-balance[s]) as usize).collect();
+        for (source, sink, amount) in min_cost_transport(&supply, &demand, distance) {
+            let path = path_from_tree(&trees[source], sinks[sink])
+                .expect("the machine is strongly connected");
+            let mut state = sources[source];
+            for input in path {
+                let next = self.transition(state, input).unwrap();
+                for _ in 0..amount {
+                    edges.push((state, input, next));
+                }
+                state = next;
+            }
+        }
+
+        // Hierholzer's algorithm.
+        let mut outgoing = vec![Vec::new(); num_states];
+        for &(from, input, to) in edges.iter().rev() {
+            outgoing[from].push((input, to));
+        }
+        let mut stack = vec![(self.initial_state(), None)];
+        let mut circuit = Vec::new();
+        while let Some(&(state, input)) = stack.last() {
+            if let Some((next_input, next)) = outgoing[state].pop() {
+                stack.push((next, Some(next_input)));
+            } else {
+                stack.pop();
+                circuit.extend(input);
+            }
+        }
+        circuit.reverse();
+        circuit
+    }
+
+    /// Cover the transitions greedily, walking to the closest transition that
+    /// was not exercised yet and resetting the machine when none is reachable.
+    fn greedy_tours(&self, edges: Vec<(usize, usize, usize)>) -> Vec<Vec<usize>> {
+        let mut uncovered: BTreeSet<(usize, usize)> = edges
+            .iter()
+            .map(|&(from, input, _)| (from, input))
+            .collect();
+        let mut tours = Vec::new();
+        let mut tour = Vec::new();
+        let mut state = self.initial_state();
+        while !uncovered.is_empty() {
+            let parents = self.shortest_path_tree(state);
+            // Transitions leading to states from which nothing else can be
+            // covered force a reset, so they are postponed.
+            let leads_to_dead_end = |from: usize, input: usize| {
+                let next = self.transition(from, input).unwrap();
+                let reachable = self.shortest_path_tree(next);
+                !uncovered
+                    .iter()
+                    .any(|&edge| edge != (from, input) && reachable[edge.0].is_some())
+            };
+            let target = uncovered
+                .iter()
+                .filter_map(|&(from, input)| Some((path_from_tree(&parents, from)?, from, input)))
+                .min_by_key(|(path, from, input)| (leads_to_dead_end(*from, *input), path.len()));
+            match target {
+                Some((path, from, input)) => {
+                    for &step in &path {
+                        uncovered.remove(&(state, step));
+                        state = self.transition(state, step).unwrap();
+                    }
+                    uncovered.remove(&(from, input));
+                    tour.extend(path);
+                    tour.push(input);
+                    state = self.transition(from, input).unwrap();
+                }
+                None => {
+                    tours.push(std::mem::take(&mut tour));
+                    state = self.initial_state();
+                }
+            }
+        }
+        if !tour.is_empty() {
+            tours.push(tour);
+        }
+        tours
+    }
+}
+
+fn path_from_tree(parents: &[Option<Option<(usize, usize)>>], state: usize) -> Option<Vec<usize>> {
+    let mut path = Vec::new();
+    let mut current = parents[state]?;
+    while let Some((previous, input)) = current {
+        path.push(input);
+        current = parents[previous].unwrap();
+    }
+    path.reverse();
+    Some(path)
+}
+
+fn remove_prefixes(mut sequences: Vec<Vec<usize>>) -> Vec<Vec<usize>> {
+    sequences.sort();
+    sequences.dedup();
+    let kept: Vec<bool> = sequences
+        .iter()
+        .enumerate()
+        .map(|(index, sequence)| {
+            !sequences
+                .iter()
+                .enumerate()
+                .any(|(other, longer)| other != index && longer.starts_with(sequence))
+        })
+        .collect();
+    sequences
+        .into_iter()
+        .zip(kept)
+        .filter_map(|(sequence, kept)| kept.then_some(sequence))
+        .collect()
+}
+
+/// Solve the transportation problem with successive shortest paths: move the
+/// `supply` of sources to cover the `demand` of sinks at the minimal total
+/// cost. Outputs `(source, sink, amount)` triples.
+fn min_cost_transport(
+    supply: &[usize],
+    demand: &[usize],
+    cost: impl Fn(usize, usize) -> usize,
+) -> Vec<(usize, usize, usize)> {
+    let (num_sources, num_sinks) = (supply.len(), demand.len());
+    let cost: Vec<Vec<i64>> = (0..num_sources)
+        .map(|source| {
+            (0..num_sinks)
+                .map(|sink| cost(source, sink) as i64)
+                .collect()
+        })
+        .collect();
+    let mut flow = vec![vec![0usize; num_sinks]; num_sources];
+    let mut supply = supply.to_vec();
+    let mut demand = demand.to_vec();
+
+    // Nodes of the residual graph: sources first, then sinks.
+    loop {
+        let num_nodes = num_sources + num_sinks;
+        let mut distance = vec![i64::MAX; num_nodes];
+        let mut previous = vec![None; num_nodes];
+        for source in 0..num_sources {
+            if supply[source] > 0 {
+                distance[source] = 0;
+            }
+        }
+        // Bellman-Ford: forward edges from sources to sinks and backward edges
+        // from sinks to sources where there is some flow.
+        for _ in 0..num_nodes {
+            let mut changed = false;
+            for source in 0..num_sources {
+                for sink in 0..num_sinks {
+                    let (u, v) = (source, num_sources + sink);
+                    if distance[u] != i64::MAX && distance[u] + cost[source][sink] < distance[v] {
+                        distance[v] = distance[u] + cost[source][sink];
+                        previous[v] = Some(u);
+                        changed = true;
+                    }
+                    if flow[source][sink] > 0
+                        && distance[v] != i64::MAX
+                        && distance[v] - cost[source][sink] < distance[u]
+                    {
+                        distance[u] = distance[v] - cost[source][sink];
+                        previous[u] = Some(v);
+                        changed = true;
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+
+        let Some(sink) = (0..num_sinks)
+            .filter(|&sink| demand[sink] > 0 && distance[num_sources + sink] != i64::MAX)
+            .min_by_key(|&sink| distance[num_sources + sink])
+        else {
+            break;
+        };
+
+        // Restore the augmenting path and find its capacity.
+        let mut path = vec![num_sources + sink];
+        while let Some(node) = previous[*path.last().unwrap()] {
+            path.push(node);
+        }
+        path.reverse();
+        let start = path[0];
+        let mut amount = supply[start].min(demand[sink]);
+        for pair in path.windows(2) {
+            if pair[0] >= num_sources {
+                amount = amount.min(flow[pair[1]][pair[0] - num_sources]);
+            }
+        }
+        for pair in path.windows(2) {
+            if pair[0] < num_sources {
+                flow[pair[0]][pair[1] - num_sources] += amount;
+            } else {
+                flow[pair[1]][pair[0] - num_sources] -= amount;
+            }
+        }
+        supply[start] -= amount;
+        demand[sink] -= amount;
+    }
+
+    (0..num_sources)
+        .flat_map(|source| (0..num_sinks).map(move |sink| (source, sink)))
+        .filter_map(|(source, sink)| {
+            let amount = flow[source][sink];
+            (amount > 0).then_some((source, sink, amount))
+        })
+        .collect()
+}
@@ -19,6 +19,20 @@ enum CircuitBreakerState {
     HalfOpen,
 }
 
+impl AtomicState for CircuitBreakerState {
+    type Atomic = std::sync::atomic::AtomicU8;
+
+    fn to_bits(&self) -> u8 {
+        *self as u8
+    }
+
+    fn from_bits(bits: u8) -> Option<Self> {
+        [Self::Closed, Self::Open, Self::HalfOpen]
+            .into_iter()
+            .find(|state| state.to_bits() == bits)
+    }
+}
+
 #[derive(Debug, PartialEq)]
 struct CircuitBreakerOutputSetTimer;
 
@@ -104,3 +118,25 @@ fn circuit_breaker() {
         assert_eq!(lock.state(), &CircuitBreakerState::Closed);
     }
 }
+
+#[test]
+fn atomic_circuit_breaker() {
+    let machine = Arc::new(AtomicStateMachine::<CircuitBreakerMachine>::new());
+    let res = machine.consume(&CircuitBreakerInput::Unsuccessful).unwrap();
+    assert_eq!(res, Some(CircuitBreakerOutputSetTimer));
+
+    let machine_wait = machine.clone();
+    std::thread::spawn(move || {
+        let res = machine_wait
+            .consume(&CircuitBreakerInput::TimerTriggered)
+            .unwrap();
+        assert_eq!(res, None);
+    })
+    .join()
+    .unwrap();
+
+    assert_eq!(machine.state(), CircuitBreakerState::HalfOpen);
+    let res = machine.consume(&CircuitBreakerInput::Successful).unwrap();
+    assert_eq!(res, None);
+    assert_eq!(machine.state(), CircuitBreakerState::Closed);
+}
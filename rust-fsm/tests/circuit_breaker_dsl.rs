@@ -37,7 +37,7 @@ fn circit_breaker_dsl() {
         let res = lock
             .consume(&circuit_breaker::Input::TimerTriggered)
             .unwrap();
-        assert!(matches!(res, None));
+        assert!(res.is_none());
         assert!(matches!(lock.state(), &circuit_breaker::State::HalfOpen));
     });
 
@@ -56,7 +56,7 @@ fn circit_breaker_dsl() {
     {
         let mut lock = machine.lock().unwrap();
         let res = lock.consume(&circuit_breaker::Input::Successful).unwrap();
-        assert!(matches!(res, None));
+        assert!(res.is_none());
         assert!(matches!(lock.state(), &circuit_breaker::State::Closed));
     }
 }
@@ -52,7 +52,7 @@ fn circit_breaker_dsl() {
         std::thread::sleep(Duration::new(5, 0));
         let mut lock = machine_wait.lock().unwrap();
         let res = lock.consume(&Input::TimerTriggered).unwrap();
-        assert!(matches!(res, None));
+        assert!(res.is_none());
         assert!(matches!(lock.state(), &State::HalfOpen));
     });
 
@@ -71,7 +71,7 @@ fn circit_breaker_dsl() {
     {
         let mut lock = machine.lock().unwrap();
         let res = lock.consume(&Input::Successful).unwrap();
-        assert!(matches!(res, None));
+        assert!(res.is_none());
         assert!(matches!(lock.state(), &State::Closed));
     }
 }
@@ -0,0 +1,29 @@
+//! Machines shared between the integration tests. Tests that need a specific
+//! shape (custom types, options, outputs) declare their own machines.
+
+// Every test binary includes this module but uses only part of it.
+#![allow(dead_code)]
+
+use rust_fsm::*;
+
+state_machine! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    pub circuit_breaker(Closed)
+
+    Closed(Unsuccessful) => Open [SetupTimer],
+    Open(TimerTriggered) => HalfOpen,
+    HalfOpen => {
+        Successful => Closed,
+        Unsuccessful => Open [SetupTimer]
+    }
+}
+
+state_machine! {
+    #[derive(Debug, PartialEq)]
+    pub door(Open)
+
+    Open(Key) => Closed,
+    Closed(Key) => Open,
+    Open(Break) => Broken,
+    Closed(Break) => Broken,
+}
@@ -0,0 +1,88 @@
+use rust_fsm::*;
+
+state_machine! {
+    #[derive(Debug, PartialEq)]
+    protocol(Idle)
+
+    Idle(Connect) => Connecting [Dial],
+    Connecting => {
+        Connected => Ready [Greet],
+        Failed => Idle
+    },
+    Ready => {
+        Send => Ready [Transmit],
+        Close => Idle [Hangup]
+    }
+}
+
+use protocol::{Impl, Input, Output, State};
+
+const SESSION: Option<State> = fold_transitions!(
+    Impl::transition,
+    State::Idle,
+    &[Input::Connect, Input::Connected, Input::Send, Input::Send],
+);
+const INVALID: Option<State> = fold_transitions!(
+    Impl::transition,
+    State::Idle,
+    &[Input::Connect, Input::Send],
+);
+const GREETING: Option<Output> = Impl::output(&State::Connecting, &Input::Connected);
+// Rejected at compile time if the sequence were invalid.
+const _: () = assert!(matches!(SESSION, Some(State::Ready)));
+
+#[test]
+fn const_functions() {
+    assert_eq!(SESSION, Some(State::Ready));
+    assert_eq!(INVALID, None);
+    assert_eq!(GREETING, Some(Output::Greet));
+    assert_eq!(
+        fold_transitions!(Impl::transition, State::Ready, &[]),
+        Some(State::Ready)
+    );
+}
+
+#[test]
+fn const_machine() {
+    static MACHINE: std::sync::Mutex<protocol::StateMachine> =
+        std::sync::Mutex::new(protocol::StateMachine::new());
+    let mut machine = MACHINE.lock().unwrap();
+    assert_eq!(
+        machine.consume(&Input::Connect).unwrap(),
+        Some(Output::Dial)
+    );
+
+    // The machine can also start from a state computed at compile time.
+    const READY: protocol::StateMachine = match SESSION {
+        Some(state) => protocol::StateMachine::from_state(state),
+        None => panic!("the session is invalid"),
+    };
+    let mut ready = READY;
+    assert_eq!(ready.consume(&Input::Close).unwrap(), Some(Output::Hangup));
+}
+
+state_machine! {
+    #[derive(Debug, PartialEq)]
+    #[state_machine(backend = "table", minimize)]
+    table(Off)
+
+    Off(Toggle) => On,
+    On(Toggle) => Off [Clicked],
+}
+
+#[test]
+fn table_backend() {
+    const ON: Option<table::State> = fold_transitions!(
+        table::Impl::transition,
+        table::State::Off,
+        &[
+            table::Input::Toggle,
+            table::Input::Toggle,
+            table::Input::Toggle
+        ],
+    );
+    assert_eq!(ON, Some(table::State::On));
+    const MINIMIZED: Option<table::minimized::State> =
+        table::minimized::Impl::transition(&table::minimized::State::On, &table::Input::Toggle);
+    assert_eq!(MINIMIZED, Some(table::minimized::State::Off));
+}
@@ -0,0 +1,63 @@
+use rust_fsm::testing::coverage::CoverageStateMachine;
+
+mod common;
+use common::circuit_breaker;
+
+#[test]
+fn partial_coverage() {
+    let mut machine = CoverageStateMachine::<circuit_breaker::Impl>::new();
+    machine
+        .consume(&circuit_breaker::Input::Unsuccessful)
+        .unwrap();
+    machine
+        .consume(&circuit_breaker::Input::TimerTriggered)
+        .unwrap();
+    assert!(machine
+        .consume(&circuit_breaker::Input::TimerTriggered)
+        .is_err());
+
+    let coverage = machine.coverage();
+    assert_eq!(coverage.covered(), 2);
+    assert_eq!(coverage.total(), 4);
+    assert_eq!(
+        coverage.uncovered(),
+        vec![("HalfOpen", "Successful"), ("HalfOpen", "Unsuccessful")]
+    );
+    coverage.assert_at_least(0.5);
+}
+
+#[test]
+#[should_panic(expected = "uncovered: HalfOpen(Successful) HalfOpen(Unsuccessful)")]
+fn coverage_below_threshold() {
+    let mut machine = CoverageStateMachine::<circuit_breaker::Impl>::new();
+    machine
+        .consume(&circuit_breaker::Input::Unsuccessful)
+        .unwrap();
+    machine
+        .consume(&circuit_breaker::Input::TimerTriggered)
+        .unwrap();
+    machine.coverage().assert_at_least(1.0);
+}
+
+#[test]
+fn merged_coverage() {
+    let mut first = CoverageStateMachine::<circuit_breaker::Impl>::new();
+    first
+        .consume(&circuit_breaker::Input::Unsuccessful)
+        .unwrap();
+    first
+        .consume(&circuit_breaker::Input::TimerTriggered)
+        .unwrap();
+    first.consume(&circuit_breaker::Input::Successful).unwrap();
+
+    let mut second =
+        CoverageStateMachine::<circuit_breaker::Impl>::from_state(circuit_breaker::State::HalfOpen);
+    second
+        .consume(&circuit_breaker::Input::Unsuccessful)
+        .unwrap();
+
+    let mut coverage = first.coverage().clone();
+    coverage.merge(second.coverage());
+    assert!(coverage.uncovered().is_empty());
+    coverage.assert_at_least(1.0);
+}
@@ -0,0 +1,106 @@
+use rust_fsm::*;
+
+state_machine! {
+    #[derive(Debug, PartialEq)]
+    #[state_machine(complete)]
+    switch(Off)
+
+    Off => {
+        Toggle => On,
+        Reset => Off
+    },
+    On => {
+        Toggle => Off [Click],
+        Reset => Off [Click]
+    }
+}
+
+state_machine! {
+    #[derive(Debug, PartialEq)]
+    #[state_machine(complete, default = ignore)]
+    ignoring(Idle)
+
+    Idle(Start) => Running,
+    Running(Stop) => Idle [Stopped],
+}
+
+state_machine! {
+    #[derive(Debug, PartialEq)]
+    #[state_machine(default = self)]
+    looping(Idle)
+
+    Idle(Start) => Running,
+    Running(Stop) => Idle [Stopped],
+}
+
+state_machine! {
+    #[derive(Debug, PartialEq)]
+    #[state_machine(complete, default = error)]
+    rejecting(Idle)
+
+    Idle(Start) => Running,
+    Running(Stop) => Idle [Stopped],
+}
+
+state_machine! {
+    #[derive(Debug, PartialEq)]
+    #[state_machine(complete)]
+    job(Queued)
+
+    Queued => {
+        Start => Running,
+        Finish => Queued
+    },
+    Running => {
+        Start => Running,
+        Finish => Done
+    },
+    // Final states do not need to handle the inputs.
+    #[state(final)]
+    Done,
+}
+
+#[test]
+fn complete() {
+    let mut machine = switch::StateMachine::new();
+    assert_eq!(machine.consume(&switch::Input::Reset).unwrap(), None);
+    machine.consume(&switch::Input::Toggle).unwrap();
+    assert_eq!(
+        machine.consume(&switch::Input::Reset).unwrap(),
+        Some(switch::Output::Click)
+    );
+
+    let mut machine = job::StateMachine::new();
+    machine.consume(&job::Input::Start).unwrap();
+    machine.consume(&job::Input::Finish).unwrap();
+    assert!(machine.consume(&job::Input::Start).is_err());
+}
+
+#[test]
+fn ignore() {
+    let mut machine = ignoring::StateMachine::new();
+    assert_eq!(machine.consume(&ignoring::Input::Stop).unwrap(), None);
+    assert_eq!(machine.state(), &ignoring::State::Idle);
+    machine.consume(&ignoring::Input::Start).unwrap();
+    assert_eq!(machine.consume(&ignoring::Input::Start).unwrap(), None);
+    assert_eq!(machine.state(), &ignoring::State::Running);
+    // The transitions are not drawn.
+    assert!(!ignoring::MERMAID.contains("Idle --> Idle"));
+}
+
+#[test]
+fn self_loop() {
+    let mut machine = looping::StateMachine::new();
+    assert_eq!(machine.consume(&looping::Input::Stop).unwrap(), None);
+    assert_eq!(machine.state(), &looping::State::Idle);
+    // The transitions are drawn.
+    assert!(looping::MERMAID.contains("    Idle --> Idle: Stop\n"));
+    assert!(looping::MERMAID.contains("    Running --> Running: Start\n"));
+}
+
+#[test]
+fn error() {
+    let mut machine = rejecting::StateMachine::new();
+    assert!(machine.consume(&rejecting::Input::Stop).is_err());
+    assert_eq!(machine.state(), &rejecting::State::Idle);
+}
@@ -0,0 +1,172 @@
+use rust_fsm::*;
+
+state_machine! {
+    #[state_machine(diagram(mermaid, dot))]
+    door(Open)
+
+    Open(Key) => Closed,
+    Closed(Key) => Open [Locked],
+    Open(Break) => Broken,
+    Closed(Break) => Broken,
+}
+
+#[test]
+fn dot() {
+    assert_eq!(
+        door::DOT,
+        r#"digraph door {
+    rankdir=LR;
+    "<start>" [shape=point];
+    Broken [shape=doublecircle];
+    Closed [shape=circle];
+    Open [shape=circle];
+    "<start>" -> Open;
+    Open -> Closed [label="Key"];
+    Closed -> Open [label="Key / Locked"];
+    Open -> Broken [label="Break"];
+    Closed -> Broken [label="Break"];
+}"#
+    );
+}
+
+state_machine! {
+    #[state_machine(export(plantuml, scxml))]
+    circuit_breaker(Closed)
+
+    Closed(Unsuccessful) => Open [SetupTimer],
+    Open(TimerTriggered) => HalfOpen,
+    HalfOpen => {
+        Successful => Closed,
+        Unsuccessful => Open [SetupTimer]
+    }
+}
+
+#[test]
+fn plantuml() {
+    assert_eq!(
+        circuit_breaker::PLANTUML,
+        "@startuml
+[*] --> Closed
+Closed --> Open : Unsuccessful / SetupTimer
+Open --> HalfOpen : TimerTriggered
+HalfOpen --> Closed : Successful
+HalfOpen --> Open : Unsuccessful / SetupTimer
+@enduml"
+    );
+}
+
+#[test]
+fn scxml() {
+    assert_eq!(
+        circuit_breaker::SCXML,
+        r##"<?xml version="1.0" encoding="UTF-8"?>
+<scxml xmlns="http://www.w3.org/2005/07/scxml" version="1.0" name="circuit_breaker" initial="Closed">
+  <state id="Closed">
+    <transition event="Unsuccessful" target="Open">
+      <send event="SetupTimer" target="#_parent"/>
+    </transition>
+  </state>
+  <state id="HalfOpen">
+    <transition event="Successful" target="Closed"/>
+    <transition event="Unsuccessful" target="Open">
+      <send event="SetupTimer" target="#_parent"/>
+    </transition>
+  </state>
+  <state id="Open">
+    <transition event="TimerTriggered" target="HalfOpen"/>
+  </state>
+</scxml>"##
+    );
+}
+
+state_machine! {
+    #[derive(Debug, PartialEq)]
+    job(Queued)
+
+    /// Waiting for a worker.
+    Queued => {
+        /// A worker picked the job up.
+        Start => Running,
+        Cancel => Cancelled
+    },
+    #[state(group(Active))]
+    Running => {
+        Finish => Done,
+        Crash => Failed [Alert]
+    },
+    /// The job completed.
+    #[state(final)]
+    Done,
+    #[state(final)]
+    Cancelled,
+    #[state(error)]
+    Failed,
+}
+
+#[test]
+fn described_states() {
+    let mut machine = job::StateMachine::new();
+    machine.consume(&job::Input::Start).unwrap();
+    let output = machine.consume(&job::Input::Crash).unwrap();
+    assert_eq!(output, Some(job::Output::Alert));
+    assert_eq!(machine.state(), &job::State::Failed);
+    // States declared without transitions do not accept any input.
+    assert!(machine.consume(&job::Input::Start).is_err());
+}
+
+#[test]
+fn mermaid() {
+    assert_eq!(
+        door::MERMAID,
+        "stateDiagram-v2
+    [*] --> Open
+    Open --> Closed: Key
+    Closed --> Open: Key [Locked]
+    Open --> Broken: Break
+    Closed --> Broken: Break
+"
+    );
+}
+
+state_machine! {
+    traffic_light(Red)
+
+    Red [Stop](Timer) => Green,
+    Green [Go](Timer) => Yellow,
+    Yellow [Slow](Timer) => Red [Flash],
+}
+
+#[test]
+fn runtime_mermaid() {
+    assert_eq!(
+        rust_fsm::diagram::mermaid::<door::Impl>(),
+        "stateDiagram-v2
+    [*] --> Open
+    Closed --> Broken: Break
+    Closed --> Open: Key [Locked]
+    Open --> Broken: Break
+    Open --> Closed: Key
+"
+    );
+
+    let mut machine = door::StateMachine::new();
+    machine.consume(&door::Input::Key).unwrap();
+    assert!(machine.mermaid().ends_with(
+        "    classDef currentState fill:#fff3cd,stroke:#ffc107,stroke-width:3px
+    class Closed currentState
+"
+    ));
+
+    // The outputs of the states are only shown inside them. The transitions
+    // are listed in a different order than in the definition.
+    let mut runtime: Vec<_> = rust_fsm::diagram::mermaid::<traffic_light::Impl>()
+        .lines()
+        .map(str::to_owned)
+        .collect();
+    let mut constant: Vec<_> = traffic_light::MERMAID.lines().map(str::to_owned).collect();
+    runtime.sort();
+    constant.sort();
+    assert_eq!(runtime, constant);
+    assert!(traffic_light::MERMAID.contains("    Red --> Green: Timer\n"));
+    assert!(traffic_light::MERMAID.contains("    Yellow --> Red: Timer [Flash]\n"));
+}
@@ -0,0 +1,78 @@
+use rust_fsm::equivalence::{check_equivalence, check_refinement, Counterexample, Reaction};
+use rust_fsm::*;
+
+mod common;
+use common::door;
+
+state_machine! {
+    door_refactored(Open)
+
+    Open => {
+        Key => Open,
+        Break => Broken
+    }
+}
+
+state_machine! {
+    door_repairable(Open)
+
+    Open(Key) => Closed,
+    Closed(Key) => Open,
+    Open(Break) => Broken,
+    Closed(Break) => Broken,
+    Broken(Repair) => Open,
+}
+
+state_machine! {
+    #[derive(Debug, PartialEq)]
+    door_alarmed(Open)
+
+    Open(Key) => Closed,
+    Closed(Key) => Open,
+    Open(Break) => Broken,
+    Closed(Break) => Broken [Alarm],
+}
+
+#[test]
+fn equivalent() {
+    check_equivalence::<door::Impl, door_refactored::Impl>().unwrap();
+    check_equivalence::<door_refactored::Impl, door::Impl>().unwrap();
+}
+
+#[test]
+fn different_outputs() {
+    let counterexample = check_equivalence::<door::Impl, door_alarmed::Impl>().unwrap_err();
+    assert_eq!(
+        counterexample,
+        Counterexample {
+            inputs: vec!["Key", "Break"],
+            left: Reaction::Accepted(vec![]),
+            right: Reaction::Accepted(vec!["Alarm"]),
+        }
+    );
+    assert_eq!(
+        counterexample.inputs_of::<door_alarmed::Impl>(),
+        Some(vec![door_alarmed::Input::Key, door_alarmed::Input::Break])
+    );
+}
+
+#[test]
+fn refinement() {
+    check_refinement::<door::Impl, door_repairable::Impl>().unwrap();
+
+    let counterexample = check_refinement::<door_repairable::Impl, door::Impl>().unwrap_err();
+    assert_eq!(counterexample.inputs, vec!["Break", "Repair"]);
+    assert_eq!(counterexample.left, Reaction::Accepted(vec![]));
+    assert_eq!(counterexample.right, Reaction::Rejected);
+
+    let counterexample = check_equivalence::<door::Impl, door_repairable::Impl>().unwrap_err();
+    assert_eq!(counterexample.inputs, vec!["Break", "Repair"]);
+    // `Repair` is not an input of the original machine.
+    assert!(counterexample.inputs_of::<door::Impl>().is_none());
+    assert_eq!(
+        counterexample
+            .inputs_of::<door_repairable::Impl>()
+            .map(|inputs| inputs.len()),
+        Some(2)
+    );
+}
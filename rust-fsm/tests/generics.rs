@@ -0,0 +1,110 @@
+use rust_fsm::{reflect::TransitionTable, *};
+
+state_machine! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    #[state_machine(minimize)]
+    pub(crate) transfer<T>(Idle) where T: Copy + std::fmt::Debug
+
+    Idle(Begin) => Sending [Opened],
+    Sending => {
+        Chunk => Sending [Written],
+        End => Done [Closed]
+    },
+    Done(Begin) => Sending [Opened],
+}
+
+#[test]
+fn type_parameters() {
+    let mut machine = transfer::StateMachine::<u8>::new();
+    assert_eq!(
+        machine.consume(&transfer::Input::Begin).unwrap(),
+        Some(transfer::Output::Opened)
+    );
+    assert_eq!(
+        machine.consume(&transfer::Input::Chunk).unwrap(),
+        Some(transfer::Output::Written)
+    );
+    assert!(machine.consume(&transfer::Input::Begin).is_err());
+    assert_eq!(machine.state(), &transfer::State::Sending);
+
+    // By value the hidden variant may be left out of the matches.
+    let state = *machine.state();
+    let name = match state {
+        transfer::State::Idle => "idle",
+        transfer::State::Sending => "sending",
+        transfer::State::Done => "done",
+    };
+    assert_eq!(name, "sending");
+}
+
+#[test]
+fn generic_items() {
+    let table = TransitionTable::of::<transfer::Impl<u8>>();
+    assert_eq!(table.state_names(), ["Done", "Idle", "Sending"]);
+    assert_eq!(
+        TransitionTable::of::<transfer::minimized::Impl<u8>>().num_states(),
+        2
+    );
+    assert_eq!(
+        transfer::minimized::map_state(&transfer::State::<u8>::Done),
+        Some(transfer::minimized::State::Idle)
+    );
+
+    // Generic machines have no `const` transition function.
+    assert_eq!(
+        <transfer::Impl<u8> as StateMachineImpl>::transition(
+            &transfer::State::Idle,
+            &transfer::Input::Begin
+        ),
+        Some(transfer::State::Sending)
+    );
+
+    let machine = transfer::AtomicStateMachine::<u8>::new();
+    machine.consume(&transfer::Input::Begin).unwrap();
+    assert_eq!(machine.state(), transfer::State::Sending);
+}
+
+pub enum Frame<'a> {
+    Open,
+    Close,
+    #[allow(dead_code)]
+    Payload(&'a [u8]),
+}
+
+state_machine! {
+    #[derive(Debug, PartialEq)]
+    #[state_machine(input(crate::Frame<'a>), backend = "table")]
+    connection<'a>(Closed)
+
+    Closed(Open) => Opened [Connected],
+    Opened(Close) => Closed,
+}
+
+#[test]
+fn lifetime_parameters() {
+    let data = vec![1, 2, 3];
+    let mut machine = connection::StateMachine::new();
+    assert_eq!(
+        machine.consume(&Frame::Open).unwrap(),
+        Some(connection::Output::Connected)
+    );
+    // The variants unknown to the machine are rejected.
+    assert!(machine.consume(&Frame::Payload(&data)).is_err());
+    assert_eq!(machine.consume(&Frame::Close).unwrap(), None);
+    assert_eq!(machine.state(), &connection::State::Closed);
+}
+
+state_machine! {
+    #[derive(Debug, PartialEq)]
+    buffer<const N: usize>(Empty)
+
+    Empty(Push) => Filled,
+    Filled(Pop) => Empty,
+}
+
+#[test]
+fn const_parameters() {
+    let mut machine = buffer::StateMachine::<16>::new();
+    machine.consume(&buffer::Input::Push).unwrap();
+    assert_eq!(machine.state(), &buffer::State::Filled);
+}
@@ -0,0 +1,36 @@
+use rust_fsm::*;
+
+state_machine!(include = "tests/fsm/circuit_breaker.fsm");
+
+mod nested {
+    use rust_fsm::*;
+
+    state_machine!(include = "tests/fsm/nested.fsm");
+}
+
+#[test]
+fn circuit_breaker() {
+    let mut machine = circuit_breaker::StateMachine::new();
+    let res = machine.consume(&circuit_breaker::Input::Unsuccessful);
+    assert_eq!(res.unwrap(), Some(circuit_breaker::Output::SetupTimer));
+    assert_eq!(machine.state(), &circuit_breaker::State::Open);
+    let res = machine.consume(&circuit_breaker::Input::TimerTriggered);
+    assert_eq!(res.unwrap(), None);
+    let res = machine.consume(&circuit_breaker::Input::Unsuccessful);
+    assert_eq!(res.unwrap(), Some(circuit_breaker::Output::SetupTimer));
+    let res = machine.consume(&circuit_breaker::Input::TimerTriggered);
+    assert_eq!(res.unwrap(), None);
+    let res = machine.consume(&circuit_breaker::Input::Successful);
+    assert_eq!(res.unwrap(), None);
+    assert_eq!(machine.state(), &circuit_breaker::State::Closed);
+}
+
+#[test]
+fn nested_include() {
+    let mut machine = nested::circuit_breaker::StateMachine::new();
+    let res = machine.consume(&nested::circuit_breaker::Input::Unsuccessful);
+    assert_eq!(
+        res.unwrap(),
+        Some(nested::circuit_breaker::Output::SetupTimer)
+    );
+}
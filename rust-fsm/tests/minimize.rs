@@ -0,0 +1,73 @@
+use rust_fsm::reflect::TransitionTable;
+use rust_fsm::*;
+
+state_machine! {
+    #[derive(Debug, PartialEq)]
+    #[state_machine(minimize)]
+    door(Open)
+
+    Open(Key) => Closed,
+    Closed(Key) => Open,
+    Open(Break) => Broken,
+    Closed(Break) => Broken,
+}
+
+state_machine! {
+    #[state_machine(warn_mergeable)]
+    circuit_breaker(Closed)
+
+    Closed(Unsuccessful) => Open [SetupTimer],
+    Open(TimerTriggered) => HalfOpen,
+    HalfOpen => {
+        Successful => Closed,
+        Unsuccessful => Open [SetupTimer]
+    }
+}
+
+#[test]
+fn reflection() {
+    assert_eq!(
+        <door::Impl as StateMachineReflect>::STATE_NAMES,
+        &["Broken", "Closed", "Open"]
+    );
+    assert_eq!(
+        <door::Impl as StateMachineReflect>::INPUT_NAMES,
+        &["Break", "Key"]
+    );
+    assert_eq!(door::Impl::state_at(1), Some(door::State::Closed));
+    assert_eq!(door::Impl::state_at(3), None);
+    assert_eq!(door::Impl::input_index(&door::Input::Key), Some(1));
+}
+
+#[test]
+fn runtime_minimization() {
+    let minimization = TransitionTable::of::<door::Impl>().minimize();
+    assert!(!minimization.is_minimal());
+    assert_eq!(minimization.mergeable_states(), vec![vec![1, 2]]);
+
+    let minimized = minimization.minimized();
+    assert_eq!(minimized.state_names(), &["Broken", "Open"]);
+    assert_eq!(minimized.initial_state(), 1);
+    assert_eq!(minimized.transition(1, 1), Some(1));
+    assert_eq!(minimized.transition(1, 0), Some(0));
+    assert_eq!(minimized.transition(0, 0), None);
+
+    let minimization = TransitionTable::of::<circuit_breaker::Impl>().minimize();
+    assert!(minimization.is_minimal());
+    assert!(minimization.mergeable_states().is_empty());
+}
+
+#[test]
+fn generated_minimized_machine() {
+    assert_eq!(
+        door::minimized::map_state(&door::State::Closed),
+        Some(door::minimized::State::Open)
+    );
+
+    let mut machine = door::minimized::StateMachine::new();
+    machine.consume(&door::Input::Key).unwrap();
+    assert_eq!(machine.state(), &door::minimized::State::Open);
+    machine.consume(&door::Input::Break).unwrap();
+    assert_eq!(machine.state(), &door::minimized::State::Broken);
+    assert!(machine.consume(&door::Input::Key).is_err());
+}
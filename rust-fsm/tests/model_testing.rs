@@ -0,0 +1,107 @@
+use proptest::{prop_assert, proptest, test_runner::Config};
+use rust_fsm::testing::proptest::{accepted_input_sequence, check_model, SystemUnderTest};
+use rust_fsm::testing::quickcheck::InputSequence;
+use rust_fsm::*;
+
+mod common;
+use common::circuit_breaker;
+
+/// A hand-written circuit breaker that is expected to follow the model.
+#[derive(Default)]
+struct Breaker {
+    open: bool,
+    half_open: bool,
+    // Accept `Successful` in the `Open` state.
+    buggy: bool,
+}
+
+impl SystemUnderTest<circuit_breaker::Impl> for Breaker {
+    fn consume(
+        &mut self,
+        input: &circuit_breaker::Input,
+    ) -> Result<Option<circuit_breaker::Output>, TransitionImpossibleError> {
+        use circuit_breaker::{Input, Output};
+        match (self.open, self.half_open, input) {
+            (false, false, Input::Unsuccessful) | (false, true, Input::Unsuccessful) => {
+                self.open = true;
+                self.half_open = false;
+                Ok(Some(Output::SetupTimer))
+            }
+            (true, _, Input::TimerTriggered) => {
+                self.open = false;
+                self.half_open = true;
+                Ok(None)
+            }
+            (false, true, Input::Successful) => {
+                self.half_open = false;
+                Ok(None)
+            }
+            (true, _, Input::Successful) if self.buggy => Ok(None),
+            _ => Err(TransitionImpossibleError),
+        }
+    }
+
+    fn check_state(&self, model_state: &circuit_breaker::State) -> bool {
+        let state = match (self.open, self.half_open) {
+            (true, _) => circuit_breaker::State::Open,
+            (false, true) => circuit_breaker::State::HalfOpen,
+            (false, false) => circuit_breaker::State::Closed,
+        };
+        &state == model_state
+    }
+}
+
+#[test]
+fn conforming_system() {
+    check_model::<circuit_breaker::Impl, _>(Config::default(), 32, Breaker::default).unwrap();
+}
+
+#[test]
+fn minimal_failing_sequence() {
+    let error = check_model::<circuit_breaker::Impl, _>(Config::default(), 32, || Breaker {
+        buggy: true,
+        ..Default::default()
+    })
+    .unwrap_err();
+    match error {
+        proptest::test_runner::TestError::Fail(_, inputs) => assert_eq!(
+            inputs,
+            vec![
+                circuit_breaker::Input::Unsuccessful,
+                circuit_breaker::Input::Successful
+            ]
+        ),
+        error => panic!("unexpected error: {error}"),
+    }
+}
+
+proptest! {
+    #[test]
+    fn accepted_sequences_are_accepted(inputs in accepted_input_sequence::<circuit_breaker::Impl>(0..32)) {
+        let mut machine = circuit_breaker::StateMachine::new();
+        for input in &inputs {
+            prop_assert!(machine.consume(input).is_ok());
+        }
+    }
+}
+
+#[test]
+fn quickcheck_sequences() {
+    fn never_half_open_after_closed(sequence: InputSequence<circuit_breaker::Impl>) -> bool {
+        let mut machine = circuit_breaker::StateMachine::new();
+        for input in sequence.inputs() {
+            let previous_closed = machine.state() == &circuit_breaker::State::Closed;
+            if machine.consume(&input).is_ok()
+                && previous_closed
+                && machine.state() == &circuit_breaker::State::HalfOpen
+            {
+                return false;
+            }
+        }
+        true
+    }
+
+    quickcheck::quickcheck(
+        never_half_open_after_closed as fn(InputSequence<circuit_breaker::Impl>) -> bool,
+    );
+}
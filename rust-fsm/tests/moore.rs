@@ -0,0 +1,142 @@
+use rust_fsm::{reflect::TransitionTable, *};
+
+mod common;
+use common::circuit_breaker;
+
+state_machine! {
+    #[derive(Debug, PartialEq)]
+    #[state_machine(minimize, export(plantuml, scxml))]
+    traffic_light(Red)
+
+    Red [Stop] => {
+        Timer => Green
+    },
+    Green [Go](Timer) => Yellow,
+    Yellow [Slow] => {
+        Timer => Red,
+        Fault => Blinking [Alert]
+    },
+    Blinking(Repair) => Red,
+}
+
+#[test]
+fn current_output() {
+    let mut machine = traffic_light::StateMachine::new();
+    assert_eq!(machine.current_output(), Some(traffic_light::Output::Stop));
+
+    // The output of the entered state is given by the transition.
+    assert_eq!(
+        machine.consume(&traffic_light::Input::Timer).unwrap(),
+        Some(traffic_light::Output::Go)
+    );
+    assert_eq!(machine.current_output(), Some(traffic_light::Output::Go));
+
+    machine.consume(&traffic_light::Input::Timer).unwrap();
+    let outputs: Vec<_> = machine
+        .consume_outputs(&traffic_light::Input::Fault)
+        .unwrap()
+        .collect();
+    assert_eq!(outputs, [traffic_light::Output::Alert]);
+    assert_eq!(machine.current_output(), None);
+
+    // The outputs of the transition come before the output of the state.
+    let outputs: Vec<_> = machine
+        .consume_outputs(&traffic_light::Input::Repair)
+        .unwrap()
+        .collect();
+    assert_eq!(outputs, [traffic_light::Output::Stop]);
+}
+
+#[test]
+fn mealy_machine_has_no_state_outputs() {
+    let mut machine = circuit_breaker::StateMachine::new();
+    assert_eq!(machine.current_output(), None);
+    machine
+        .consume(&circuit_breaker::Input::Unsuccessful)
+        .unwrap();
+    assert_eq!(machine.current_output(), None);
+}
+
+#[test]
+fn transition_and_state_outputs() {
+    state_machine! {
+        #[derive(Debug, PartialEq)]
+        door(Closed)
+
+        Closed [Locked](Open) => Opened [Beep],
+        Opened [Unlocked](Close) => Closed,
+    }
+
+    let mut machine = door::StateMachine::new();
+    let outputs: Vec<_> = machine
+        .consume_outputs(&door::Input::Open)
+        .unwrap()
+        .collect();
+    assert_eq!(outputs, [door::Output::Beep, door::Output::Unlocked]);
+    assert_eq!(
+        machine.consume(&door::Input::Close).unwrap(),
+        Some(door::Output::Locked)
+    );
+}
+
+#[test]
+fn diagrams() {
+    assert!(traffic_light::MERMAID.contains("    Red: entry / Stop\n"));
+    // The outputs of the states are not repeated on the transitions.
+    assert!(traffic_light::MERMAID.contains("Yellow --> Red: Timer\n"));
+    assert!(traffic_light::DOT.contains("Green [shape=circle, label=\"Green\\nentry / Go\"];"));
+    assert!(traffic_light::PLANTUML.contains("Yellow : entry / Slow\n"));
+    assert!(traffic_light::SCXML.contains(
+        r##"  <state id="Green">
+    <onentry>
+      <send event="Go" target="#_parent"/>
+    </onentry>"##
+    ));
+
+    let diagram = diagram::mermaid::<traffic_light::Impl>();
+    assert!(diagram.contains("    Red: entry / Stop\n"));
+    assert!(!diagram.contains("Blinking: entry"));
+}
+
+state_machine! {
+    #[derive(Debug, PartialEq)]
+    #[state_machine(minimize)]
+    blinker(On)
+
+    On [Light](Tick) => Off,
+    Off(Tick) => AlsoOn,
+    AlsoOn [Light](Tick) => AlsoOff,
+    AlsoOff(Tick) => On,
+}
+
+#[test]
+fn minimization_keeps_state_outputs() {
+    let table = TransitionTable::of::<blinker::Impl>();
+    let light = table.output_names().iter().position(|&o| o == "Light");
+    let on = table.state_names().iter().position(|&s| s == "On").unwrap();
+    assert_eq!(table.state_output(on), light);
+
+    let minimization = table.minimize();
+    assert_eq!(minimization.num_classes(), 2);
+    let minimized = minimization.minimized();
+    assert_eq!(minimized.state_output(minimized.initial_state()), light);
+    assert_eq!(TransitionTable::of::<blinker::minimized::Impl>(), minimized);
+
+    let machine = blinker::minimized::StateMachine::new();
+    assert_eq!(machine.current_output(), Some(blinker::Output::Light));
+}
+
+#[test]
+fn states_with_different_outputs_are_not_merged() {
+    // Without their own outputs `A` and `B` would be equivalent.
+    state_machine! {
+        fork(A)
+
+        A [Low](Next) => C,
+        B [High](Next) => C,
+        C(Next) => C,
+    }
+
+    let minimization = TransitionTable::of::<fork::Impl>().minimize();
+    assert!(minimization.is_minimal());
+}
@@ -0,0 +1,139 @@
+use rust_fsm::{reflect::TransitionTable, *};
+
+mod common;
+use common::circuit_breaker;
+
+state_machine! {
+    #[derive(Debug, PartialEq)]
+    #[state_machine(export(scxml))]
+    connection(Connecting)
+
+    Connecting => {
+        Connected => Open [CancelTimer, NotifyPeer],
+        TimedOut => Closed [NotifyPeer]
+    },
+    Open(Close) => Closed [NotifyPeer, Log, CancelTimer],
+}
+
+#[test]
+fn consume_outputs() {
+    let mut machine = connection::StateMachine::new();
+    let outputs: Vec<_> = machine
+        .consume_outputs(&connection::Input::Connected)
+        .unwrap()
+        .collect();
+    assert_eq!(
+        outputs,
+        [
+            connection::Output::CancelTimer,
+            connection::Output::NotifyPeer
+        ]
+    );
+    assert_eq!(machine.state(), &connection::State::Open);
+
+    // `consume` gives the first output.
+    assert_eq!(
+        machine.consume(&connection::Input::Close).unwrap(),
+        Some(connection::Output::NotifyPeer)
+    );
+    assert!(machine.consume_outputs(&connection::Input::Close).is_err());
+}
+
+#[test]
+fn single_output() {
+    let mut machine = connection::StateMachine::new();
+    let outputs: Vec<_> = machine
+        .consume_outputs(&connection::Input::TimedOut)
+        .unwrap()
+        .collect();
+    assert_eq!(outputs, [connection::Output::NotifyPeer]);
+
+    // Machines declared with single outputs yield them the same way.
+    let mut machine = circuit_breaker::StateMachine::new();
+    let outputs: Vec<_> = machine
+        .consume_outputs(&circuit_breaker::Input::Unsuccessful)
+        .unwrap()
+        .collect();
+    assert_eq!(outputs, [circuit_breaker::Output::SetupTimer]);
+}
+
+#[test]
+fn diagrams() {
+    assert!(
+        connection::MERMAID.contains("Connecting --> Open: Connected [CancelTimer, NotifyPeer]")
+    );
+    assert!(connection::DOT.contains("[label=\"Close / NotifyPeer, Log, CancelTimer\"]"));
+    assert!(connection::SCXML.contains(
+        r##"    <transition event="Connected" target="Open">
+      <send event="CancelTimer" target="#_parent"/>
+      <send event="NotifyPeer" target="#_parent"/>
+    </transition>"##
+    ));
+}
+
+state_machine! {
+    #[derive(Debug, PartialEq)]
+    #[state_machine(minimize)]
+    relay(A)
+
+    A(Next) => B [Ack, Left],
+    B(Next) => A [Ack, Right],
+    C(Next) => B [Ack, Left],
+}
+
+#[test]
+fn analysis_sees_all_outputs() {
+    let table = TransitionTable::of::<relay::Impl>();
+    let ack = table
+        .output_names()
+        .iter()
+        .position(|&o| o == "Ack")
+        .unwrap();
+    let left = table
+        .output_names()
+        .iter()
+        .position(|&o| o == "Left")
+        .unwrap();
+    assert_eq!(table.output(0, 0), Some(ack));
+    assert_eq!(table.outputs(0, 0), [ack, left]);
+
+    // `B` differs from the other states in the second output only, while `A`
+    // and `C` are equivalent.
+    let minimization = table.minimize();
+    assert_eq!(minimization.num_classes(), 2);
+    assert_eq!(minimization.mergeable_states(), [vec![0, 2]]);
+    assert_eq!(
+        relay::minimized::StateMachine::new().state(),
+        &relay::minimized::State::A
+    );
+}
+
+/// A machine implemented by hand giving several outputs without allocation.
+struct Blinker;
+
+impl StateMachineImpl for Blinker {
+    type Input = ();
+    type State = bool;
+    type Output = &'static str;
+    const INITIAL_STATE: Self::State = false;
+
+    fn transition(state: &Self::State, _: &Self::Input) -> Option<Self::State> {
+        Some(!state)
+    }
+
+    fn output(state: &Self::State, input: &Self::Input) -> Option<Self::Output> {
+        Self::nth_output(state, input, 0)
+    }
+
+    fn nth_output(state: &Self::State, _: &Self::Input, index: usize) -> Option<Self::Output> {
+        let outputs: &[&str] = if *state { &["off"] } else { &["on", "beep"] };
+        outputs.get(index).copied()
+    }
+}
+
+#[test]
+fn manual_implementation() {
+    let mut machine = StateMachine::<Blinker>::new();
+    assert!(machine.consume_outputs(&()).unwrap().eq(["on", "beep"]));
+    assert!(machine.consume_outputs(&()).unwrap().eq(["off"]));
+}
@@ -0,0 +1,145 @@
+use rust_fsm::*;
+
+mod protocol {
+    use rust_fsm::*;
+    use std::fmt::Debug;
+
+    #[derive(Debug, PartialEq)]
+    pub enum Input {
+        Connect,
+        Disconnect,
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub enum State {
+        Offline,
+        Online,
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub enum Frame<T> {
+        Hello,
+        #[allow(dead_code)]
+        Data(T),
+    }
+
+    state_machine! {
+        #[state_machine(input(Input), state(super::State), output(Frame<T>), minimize)]
+        pub session<T>(Offline) where T: Debug
+
+        Offline(Connect) => Online [Hello],
+        Online(Disconnect) => Offline,
+    }
+}
+
+use protocol::{session, Frame, Input, State};
+
+#[test]
+fn relative_paths() {
+    let mut machine = session::StateMachine::<u8>::new();
+    assert_eq!(
+        machine.consume(&Input::Connect).unwrap(),
+        Some(Frame::Hello)
+    );
+    assert_eq!(machine.state(), &State::Online);
+
+    let mut machine = session::minimized::StateMachine::<u8>::new();
+    assert_eq!(
+        machine.consume(&Input::Connect).unwrap(),
+        Some(Frame::Hello)
+    );
+    assert!(machine.consume(&Input::Connect).is_err());
+}
+
+// The generated types shadow the items of the enclosing scope with the same
+// names.
+#[allow(dead_code)]
+enum Output {
+    Unrelated,
+}
+
+state_machine! {
+    #[derive(Debug, PartialEq)]
+    door(Open)
+
+    Open(Close) => Closed [Closing],
+}
+
+#[test]
+fn shadowing() {
+    let mut machine = door::StateMachine::new();
+    assert_eq!(
+        machine.consume(&door::Input::Close).unwrap(),
+        Some(door::Output::Closing)
+    );
+}
+
+// The enclosing scope may shadow the prelude, the generated code does not rely
+// on it.
+mod prelude {
+    use rust_fsm::*;
+
+    #[allow(dead_code)]
+    pub enum Lookup {
+        Some(u8),
+        None,
+    }
+
+    #[allow(unused_imports)]
+    use Lookup::*;
+
+    state_machine! {
+        #[derive(Debug, PartialEq)]
+        #[state_machine(minimize)]
+        pub light(Off)
+
+        Off [Dark],
+        On [Bright],
+        Off(Toggle) => On [Click, Hum],
+        On(Toggle) => Off,
+    }
+
+    state_machine! {
+        #[derive(Debug, PartialEq)]
+        #[state_machine(backend = "table", minimize)]
+        pub switch(Off)
+
+        Off(Toggle) => On [Click, Hum],
+        On(Toggle) => Off,
+    }
+}
+
+#[test]
+fn shadowed_prelude() {
+    use prelude::{light, switch};
+
+    let mut machine = light::StateMachine::new();
+    assert_eq!(
+        machine
+            .consume_outputs(&light::Input::Toggle)
+            .unwrap()
+            .collect::<Vec<_>>(),
+        [
+            light::Output::Click,
+            light::Output::Hum,
+            light::Output::Bright
+        ]
+    );
+    assert_eq!(
+        light::minimized::map_state(&light::State::On),
+        Some(light::minimized::State::On)
+    );
+
+    let mut machine = switch::StateMachine::new();
+    assert_eq!(
+        machine
+            .consume_outputs(&switch::Input::Toggle)
+            .unwrap()
+            .collect::<Vec<_>>(),
+        [switch::Output::Click, switch::Output::Hum]
+    );
+    assert_eq!(
+        switch::Impl::transition(&switch::State::On, &switch::Input::Toggle),
+        Some(switch::State::Off)
+    );
+}
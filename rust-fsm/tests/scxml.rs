@@ -0,0 +1,77 @@
+use rust_fsm::*;
+
+state_machine_from_file! {
+    #[derive(Debug, PartialEq)]
+    "tests/scxml/circuit_breaker.scxml"
+}
+
+state_machine_from_file! {
+    #[derive(Debug, PartialEq)]
+    pub door = "tests/scxml/door.scxml"
+}
+
+state_machine_from_file! {
+    #[derive(Debug, PartialEq)]
+    "tests/scxml/light.scxml"
+}
+
+state_machine_from_file! {
+    #[derive(Debug, PartialEq)]
+    #[state_machine(export(scxml))]
+    "tests/scxml/alarm.scxml"
+}
+
+#[test]
+fn circuit_breaker() {
+    let mut machine = circuit_breaker::StateMachine::new();
+    assert_eq!(machine.state(), &circuit_breaker::State::Closed);
+    let res = machine.consume(&circuit_breaker::Input::Unsuccessful);
+    assert_eq!(res.unwrap(), Some(circuit_breaker::Output::SetupTimer));
+    assert_eq!(machine.state(), &circuit_breaker::State::Open);
+    let res = machine.consume(&circuit_breaker::Input::TimerTriggered);
+    assert_eq!(res.unwrap(), None);
+    let res = machine.consume(&circuit_breaker::Input::Successful);
+    assert_eq!(res.unwrap(), None);
+    assert_eq!(machine.state(), &circuit_breaker::State::Closed);
+}
+
+#[test]
+fn door() {
+    assert_eq!(door::Impl::STATE_NAMES, &["Broken", "Closed", "Open"]);
+    let mut machine = door::StateMachine::new();
+    assert_eq!(machine.state(), &door::State::Open);
+    assert_eq!(machine.consume(&door::Input::Key).unwrap(), None);
+    assert_eq!(
+        machine.consume(&door::Input::Key).unwrap(),
+        Some(door::Output::Locked)
+    );
+    assert_eq!(machine.consume(&door::Input::Break).unwrap(), None);
+    assert_eq!(machine.state(), &door::State::Broken);
+    assert!(machine.consume(&door::Input::Key).is_err());
+}
+
+#[test]
+fn light() {
+    let mut machine = light::StateMachine::new();
+    assert_eq!(machine.current_output(), Some(light::Output::Dark));
+    assert_eq!(
+        machine.consume(&light::Input::Toggle).unwrap(),
+        Some(light::Output::Bright)
+    );
+    assert_eq!(machine.current_output(), Some(light::Output::Bright));
+}
+
+#[test]
+fn round_trip() {
+    // The exported document is the one the machine was imported from.
+    assert_eq!(alarm::SCXML, include_str!("scxml/alarm.scxml").trim_end());
+
+    let mut machine = alarm::StateMachine::new();
+    assert_eq!(machine.current_output(), Some(alarm::Output::Quiet));
+    machine.consume(&alarm::Input::Arm).unwrap();
+    let outputs: Vec<_> = machine
+        .consume_outputs(&alarm::Input::Intrusion)
+        .unwrap()
+        .collect();
+    assert_eq!(outputs, [alarm::Output::Siren, alarm::Output::Call]);
+}
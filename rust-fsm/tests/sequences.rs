@@ -0,0 +1,91 @@
+use rust_fsm::reflect::TransitionTable;
+use rust_fsm::testing::coverage::CoverageStateMachine;
+use rust_fsm::testing::sequences::{characterization_set, state_cover, transition_tour, w_method};
+use rust_fsm::*;
+
+mod common;
+use common::{circuit_breaker, door};
+
+#[test]
+fn shortest_paths() {
+    use circuit_breaker::Input::*;
+    assert_eq!(
+        state_cover::<circuit_breaker::Impl>(),
+        vec![
+            Some(vec![]),
+            Some(vec![Unsuccessful, TimerTriggered]),
+            Some(vec![Unsuccessful]),
+        ]
+    );
+}
+
+#[test]
+fn postman_tour() {
+    let tours = transition_tour::<circuit_breaker::Impl>();
+    assert_eq!(tours.len(), 1);
+    // The `Open -> HalfOpen` transition has to be taken twice:
+    // Closed -> Open -> HalfOpen -> Open -> HalfOpen -> Closed
+    assert_eq!(tours[0].len(), 5);
+
+    let mut machine = CoverageStateMachine::<circuit_breaker::Impl>::new();
+    for input in &tours[0] {
+        machine.consume(input).unwrap();
+    }
+    assert_eq!(machine.state(), &circuit_breaker::State::Closed);
+    machine.coverage().assert_at_least(1.0);
+}
+
+#[test]
+fn tour_with_resets() {
+    let tours = transition_tour::<door::Impl>();
+    let mut coverage = None;
+    for tour in &tours {
+        let mut machine = CoverageStateMachine::<door::Impl>::new();
+        for input in tour {
+            machine.consume(input).unwrap();
+        }
+        coverage
+            .get_or_insert_with(|| machine.coverage().clone())
+            .merge(machine.coverage());
+    }
+    coverage.unwrap().assert_at_least(1.0);
+    assert_eq!(tours.len(), 2);
+}
+
+#[test]
+fn characterization() {
+    use circuit_breaker::Input::*;
+    let set = characterization_set::<circuit_breaker::Impl>();
+    let table = TransitionTable::of::<circuit_breaker::Impl>();
+    // Every pair of states reacts differently to some sequence.
+    for first in 0..table.num_states() {
+        for second in first + 1..table.num_states() {
+            assert!(set.iter().any(|sequence| {
+                let run = |state| {
+                    let mut machine = circuit_breaker::StateMachine::from_state(state);
+                    sequence
+                        .iter()
+                        .map(|input| machine.consume(input).ok())
+                        .collect::<Vec<_>>()
+                };
+                run(circuit_breaker::Impl::state_at(first).unwrap())
+                    != run(circuit_breaker::Impl::state_at(second).unwrap())
+            }));
+        }
+    }
+    assert_eq!(set, vec![vec![Successful], vec![TimerTriggered]]);
+}
+
+#[test]
+fn w_method_suite() {
+    let suite = w_method::<circuit_breaker::Impl>(0);
+    assert!(!suite.is_empty());
+    for sequence in &suite {
+        // Sequences are applicable to the specification itself.
+        let mut machine = circuit_breaker::StateMachine::new();
+        for input in sequence {
+            let _ = machine.consume(input);
+        }
+    }
+    assert!(w_method::<circuit_breaker::Impl>(1).len() > suite.len());
+}
@@ -0,0 +1,146 @@
+use rust_fsm::*;
+
+mod common;
+use common::circuit_breaker;
+use std::{sync::Arc, thread};
+
+#[test]
+fn shared() {
+    let machine = Arc::new(SharedStateMachine::<circuit_breaker::Impl>::new());
+    assert!(machine
+        .consume(&circuit_breaker::Input::Successful)
+        .is_err());
+
+    // Only one of the threads opens the circuit.
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let machine = machine.clone();
+            thread::spawn(move || machine.consume(&circuit_breaker::Input::Unsuccessful))
+        })
+        .collect();
+    let opened = handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .filter(Result::is_ok)
+        .count();
+    assert_eq!(opened, 1);
+    assert_eq!(machine.state(), circuit_breaker::State::Open);
+
+    assert_eq!(
+        machine
+            .consume(&circuit_breaker::Input::TimerTriggered)
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        machine
+            .consume_outputs(&circuit_breaker::Input::Unsuccessful)
+            .unwrap(),
+        [circuit_breaker::Output::SetupTimer]
+    );
+
+    let machine = Arc::try_unwrap(machine).unwrap().into_inner();
+    assert_eq!(machine.state(), &circuit_breaker::State::Open);
+}
+
+#[test]
+fn atomic() {
+    let machine = Arc::new(circuit_breaker::AtomicStateMachine::new());
+    assert!(machine
+        .consume(&circuit_breaker::Input::Successful)
+        .is_err());
+
+    let handles: Vec<_> = (0..8)
+        .map(|_| {
+            let machine = machine.clone();
+            thread::spawn(move || machine.consume(&circuit_breaker::Input::Unsuccessful))
+        })
+        .collect();
+    let outputs: Vec<_> = handles
+        .into_iter()
+        .filter_map(|handle| handle.join().unwrap().ok())
+        .collect();
+    assert_eq!(outputs, [Some(circuit_breaker::Output::SetupTimer)]);
+    assert_eq!(machine.state(), circuit_breaker::State::Open);
+
+    machine
+        .consume(&circuit_breaker::Input::TimerTriggered)
+        .unwrap();
+    let machine = Arc::try_unwrap(machine).unwrap().into_inner();
+    assert_eq!(machine.state(), &circuit_breaker::State::HalfOpen);
+}
+
+#[test]
+fn atomic_contention() {
+    state_machine! {
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        counter(Zero)
+
+        Zero(Tick) => One,
+        One(Tick) => Two,
+        Two(Tick) => Zero [Wrapped],
+    }
+
+    // Every input is accepted exactly once, whatever the interleaving.
+    let machine = Arc::new(counter::AtomicStateMachine::from(
+        counter::StateMachine::new(),
+    ));
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let machine = machine.clone();
+            thread::spawn(move || {
+                (0..300)
+                    .filter(|_| machine.consume(&counter::Input::Tick).unwrap().is_some())
+                    .count()
+            })
+        })
+        .collect();
+    let wrapped: usize = handles.into_iter().map(|h| h.join().unwrap()).sum();
+    assert_eq!(wrapped, 400);
+    assert_eq!(machine.state(), counter::State::Zero);
+}
+
+#[test]
+fn atomic_outputs() {
+    state_machine! {
+        #[derive(Debug, PartialEq)]
+        connection(Closed)
+
+        Closed(Open) => Connecting [StartTimer],
+        Connecting(Connected) => Open [CancelTimer, NotifyPeer],
+        Open [Ready](Close) => Closed,
+    }
+
+    let machine = connection::AtomicStateMachine::new();
+    assert!(machine.consume_outputs(&connection::Input::Close).is_err());
+    assert_eq!(
+        machine
+            .consume_outputs(&connection::Input::Open)
+            .unwrap()
+            .collect::<Vec<_>>(),
+        [connection::Output::StartTimer]
+    );
+    // The outputs of the transition are followed by the output of the state.
+    assert_eq!(
+        machine
+            .consume_outputs(&connection::Input::Connected)
+            .unwrap()
+            .collect::<Vec<_>>(),
+        [
+            connection::Output::CancelTimer,
+            connection::Output::NotifyPeer,
+            connection::Output::Ready
+        ]
+    );
+    assert_eq!(machine.state(), connection::State::Open);
+}
+
+#[test]
+fn generated_conversions() {
+    use circuit_breaker::State;
+
+    for state in [State::Closed, State::HalfOpen, State::Open] {
+        assert_eq!(State::from_bits(state.to_bits()), Some(state));
+    }
+    assert_eq!(State::from_bits(3), None);
+}
@@ -0,0 +1,146 @@
+use rust_fsm::{reflect::TransitionTable, *};
+
+state_machine! {
+    #[derive(Debug, PartialEq)]
+    #[state_machine(minimize, default = self)]
+    with_match(Idle)
+
+    Idle [Ready] => {
+        Start => Running [Started],
+        Stop => Idle
+    },
+    Running => {
+        Pause => Paused,
+        Stop => Idle [Stopped, Cleanup],
+        Fail => Failed [Alert]
+    },
+    Paused => {
+        Resume => Running,
+        Stop => Idle [Stopped, Cleanup]
+    },
+    Failed(Reset) => Idle,
+}
+
+state_machine! {
+    #[derive(Debug, PartialEq)]
+    #[state_machine(minimize, default = self, backend = "table")]
+    with_table(Idle)
+
+    Idle [Ready] => {
+        Start => Running [Started],
+        Stop => Idle
+    },
+    Running => {
+        Pause => Paused,
+        Stop => Idle [Stopped, Cleanup],
+        Fail => Failed [Alert]
+    },
+    Paused => {
+        Resume => Running,
+        Stop => Idle [Stopped, Cleanup]
+    },
+    Failed(Reset) => Idle,
+}
+
+#[test]
+fn same_behavior() {
+    assert_eq!(
+        TransitionTable::of::<with_table::Impl>(),
+        TransitionTable::of::<with_match::Impl>()
+    );
+    assert_eq!(
+        TransitionTable::of::<with_table::minimized::Impl>(),
+        TransitionTable::of::<with_match::minimized::Impl>()
+    );
+}
+
+#[test]
+fn consume() {
+    let mut machine = with_table::StateMachine::new();
+    assert_eq!(machine.current_output(), Some(with_table::Output::Ready));
+    assert_eq!(
+        machine.consume(&with_table::Input::Start).unwrap(),
+        Some(with_table::Output::Started)
+    );
+    let outputs: Vec<_> = machine
+        .consume_outputs(&with_table::Input::Stop)
+        .unwrap()
+        .collect();
+    assert_eq!(
+        outputs,
+        [
+            with_table::Output::Stopped,
+            with_table::Output::Cleanup,
+            with_table::Output::Ready
+        ]
+    );
+    // The default policy fills the table.
+    assert_eq!(machine.consume(&with_table::Input::Resume).unwrap(), None);
+    assert_eq!(machine.state(), &with_table::State::Idle);
+}
+
+state_machine! {
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    #[state_machine(backend = "table")]
+    switch(Off)
+
+    Off(Toggle) => On [Click],
+    On(Toggle) => Off,
+}
+
+#[test]
+fn integer_enums() {
+    assert_eq!(std::mem::size_of::<with_table::State>(), 2);
+    assert_eq!(with_table::State::Running as u16, 3);
+    assert_eq!(with_table::Input::Fail as u16, 0);
+
+    // The enums are `Copy` whether the definition derives it or not.
+    fn copy<T: Copy>(value: &T) -> T {
+        *value
+    }
+    assert_eq!(copy(&with_table::State::Paused), with_table::State::Paused);
+    let mut machine = switch::StateMachine::new();
+    assert_eq!(
+        machine.consume(&copy(&switch::Input::Toggle)).unwrap(),
+        Some(switch::Output::Click)
+    );
+    assert_eq!(copy(machine.state()), switch::State::On);
+}
+
+pub enum Input {
+    Successful,
+    Unsuccessful,
+    TimerTriggered,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum State {
+    Closed,
+    HalfOpen,
+    Open,
+}
+
+state_machine! {
+    #[state_machine(input(crate::Input), state(crate::State), backend = "table")]
+    circuit_breaker(Closed)
+
+    Closed(Unsuccessful) => Open [SetupTimer],
+    Open(TimerTriggered) => HalfOpen,
+    HalfOpen => {
+        Successful => Closed,
+        Unsuccessful => Open [SetupTimer]
+    }
+}
+
+#[test]
+fn custom_types() {
+    let mut machine = circuit_breaker::StateMachine::new();
+    assert!(matches!(
+        machine.consume(&Input::Unsuccessful),
+        Ok(Some(circuit_breaker::Output::SetupTimer))
+    ));
+    assert!(machine.consume(&Input::Successful).is_err());
+    assert!(matches!(machine.consume(&Input::TimerTriggered), Ok(None)));
+    assert!(matches!(machine.consume(&Input::Successful), Ok(None)));
+    assert_eq!(machine.state(), &State::Closed);
+}
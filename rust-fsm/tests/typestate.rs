@@ -0,0 +1,74 @@
+use rust_fsm::*;
+
+state_machine! {
+    #[derive(Debug, PartialEq)]
+    #[state_machine(typestate)]
+    circuit_breaker(Closed)
+
+    Closed(Unsuccessful) => Open [SetupTimer],
+    Open(TimerTriggered) => HalfOpen,
+    HalfOpen => {
+        Successful => Closed,
+        Unsuccessful => Open [SetupTimer]
+    }
+}
+
+#[test]
+fn transitions() {
+    use circuit_breaker::typestate::{HalfOpen, Machine, Open};
+
+    let machine = Machine::new();
+    let (machine, output): (Machine<Open>, _) = machine.unsuccessful();
+    assert_eq!(output, circuit_breaker::Output::SetupTimer);
+    let machine: Machine<HalfOpen> = machine.timer_triggered();
+    let machine = machine.successful();
+
+    // The machine can continue as the enum-based one.
+    let mut machine = circuit_breaker::StateMachine::from(machine);
+    assert_eq!(machine.state(), &circuit_breaker::State::Closed);
+    assert_eq!(
+        machine
+            .consume(&circuit_breaker::Input::Unsuccessful)
+            .unwrap(),
+        Some(circuit_breaker::Output::SetupTimer)
+    );
+}
+
+#[test]
+fn zero_sized() {
+    use circuit_breaker::typestate::{Closed, Machine, TypeState};
+
+    assert_eq!(std::mem::size_of::<Machine<Closed>>(), 0);
+    assert_eq!(Closed::STATE, circuit_breaker::State::Closed);
+}
+
+state_machine! {
+    #[derive(Debug, PartialEq)]
+    #[state_machine(typestate, default = self)]
+    connection(Idle)
+
+    Idle(HTTPRequest) => Busy [Accept, Log],
+    /// The response is sent.
+    Busy(SendResponse) => Idle [Log],
+    Busy(Type) => Idle,
+}
+
+#[test]
+fn method_names_and_outputs() {
+    use connection::typestate::Machine;
+
+    let (machine, outputs) = Machine::new().http_request();
+    assert_eq!(
+        outputs,
+        [connection::Output::Accept, connection::Output::Log]
+    );
+    let (machine, output) = machine.send_response();
+    assert_eq!(output, connection::Output::Log);
+    // The default policy adds the missing transitions.
+    let machine = machine.send_response();
+    let machine = machine.http_request().0.r#type();
+    assert_eq!(
+        connection::StateMachine::from(machine).state(),
+        &connection::State::Idle
+    );
+}
@@ -0,0 +1,16 @@
+// The warnings about equivalent states are denied to check their notes.
+#![deny(deprecated)]
+
+use rust_fsm::*;
+
+state_machine! {
+    #[state_machine(warn_mergeable)]
+    door(Open)
+
+    Open(Key) => Closed,
+    Closed(Key) => Open,
+    Open(Break) => Broken,
+    Closed(Break) => Broken,
+}
+
+fn main() {}
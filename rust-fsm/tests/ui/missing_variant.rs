@@ -0,0 +1,20 @@
+use rust_fsm::*;
+
+pub enum Input {
+    Push,
+}
+
+pub enum State {
+    Locked,
+    Unlocked,
+}
+
+state_machine! {
+    #[state_machine(input(Input), state(State))]
+    turnstile(Locked)
+
+    Locked(Coin) => Unlocked,
+    Unlocked(Push) => Locked,
+}
+
+fn main() {}